@@ -0,0 +1,203 @@
+//! Logic for a *quiet* build via `--clean-output`, which shows a progress
+//! spinner in place of the `cargo build` output, and only prints compiler
+//! diagnostics if the build fails.
+//!
+use crate::*;
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use colored::Colorize;
+
+/// How often to redraw the progress spinner
+const TICK: Duration = Duration::from_millis(80);
+
+/// Frames of the progress spinner
+#[cfg(not(target_family = "windows"))]
+const FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// Frames of the progress spinner
+#[cfg(target_family = "windows")]
+const FRAMES: &[&str] = &["|", "/", "-", "\\"];
+
+/// Status lines which `cargo` prints on *stderr* while it builds, which are
+/// hidden from the output on a failed build.
+const PROGRESS_LINES: &[&str] = &[
+    "Compiling",
+    "Checking",
+    "Fresh",
+    "Finished",
+    "Blocking",
+    "Updating",
+    "Locking",
+    "Adding",
+    "Downloading",
+    "Downloaded",
+];
+
+/// A single-line progress *spinner*, which is only shown if *stderr* is a
+/// terminal.
+struct Spinner {
+    /// True if the spinner is shown
+    enabled: bool,
+
+    /// Index of the current frame
+    frame: usize,
+}
+
+impl Spinner {
+    /// Create a new `Spinner`.
+    fn new() -> Self {
+        Self {
+            enabled: atty::is(atty::Stream::Stderr),
+            frame: 0,
+        }
+    }
+
+    /// Redraw the spinner with the next frame, along with a message `msg`.
+    fn tick(&mut self, msg: &str) {
+        if !self.enabled {
+            return;
+        }
+        let frame = FRAMES[self.frame % FRAMES.len()];
+        self.frame += 1;
+
+        let mut stderr = std::io::stderr();
+        let _ = write!(stderr, "\r\x1b[2K{} {}", frame.cyan().bold(), msg.dimmed());
+        let _ = stderr.flush();
+    }
+
+    /// Clear the spinner from the terminal.
+    fn clear(&self) {
+        if self.enabled {
+            eprint!("\r\x1b[2K");
+        }
+    }
+}
+
+/// The output of a `cargo build`, as parsed from its JSON messages.
+pub(crate) struct BuildOutput {
+    /// The `cargo build` command line, as displayed
+    pub command: String,
+
+    /// The exit status of `cargo`
+    pub status: ExitStatus,
+
+    /// The target and path of each *executable* which was built
+    pub executables: Vec<(CargoTarget, PathBuf)>,
+
+    /// The compiler diagnostics which point to a location in the source code
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Return the `--message-format` to pass to `cargo build`, so we can parse
+/// its output -- which renders the diagnostics with colors if they're
+/// enabled.
+pub(crate) fn build_message_format() -> &'static str {
+    if colored::control::SHOULD_COLORIZE.should_colorize() {
+        "json-diagnostic-rendered-ansi"
+    } else {
+        "json"
+    }
+}
+
+/// Run the `cargo build` command `build` for the project at `root_path`
+/// quietly, showing a progress spinner rather than its output; any errors
+/// and warnings are only printed if the build fails.
+///
+/// Note that `build` should be called with the
+/// [`build_message_format`], so we can parse its output.
+pub(crate) fn build_quietly(build: &mut Command, root_path: &Path) -> Result<BuildOutput> {
+    let mut child = build
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let stdout = child.stdout.take().expect("Failed to open stdout");
+    let mut stderr = child.stderr.take().expect("Failed to open stderr");
+
+    // read the messages in a separate thread, so the spinner keeps spinning
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(|line| line.ok()) {
+            if let Ok(msg) = serde_json::from_str::<CargoMessage>(&line) {
+                if tx.send(msg).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+    let stderr = thread::spawn(move || {
+        let mut output = String::new();
+        let _ = stderr.read_to_string(&mut output);
+        output
+    });
+
+    let mut spinner = Spinner::new();
+    let mut status_msg = "Building...".to_owned();
+    let mut compiled = 0;
+    let mut executables = Vec::new();
+    let mut diagnostics = Vec::new();
+    let mut rendered = Vec::new();
+    let mut warnings = 0;
+
+    loop {
+        match rx.recv_timeout(TICK) {
+            Ok(msg) => {
+                if let (Some(exe), Some(target)) = (msg.executable(), msg.target.as_ref()) {
+                    executables.push((target.clone(), exe.to_owned()));
+                }
+                match (msg.reason.as_str(), &msg.target, &msg.message) {
+                    ("compiler-artifact", Some(target), _) => {
+                        compiled += 1;
+                        status_msg = format!("Building... {compiled} compiled ({})", target.name);
+                    }
+                    ("compiler-message", _, Some(message)) => {
+                        if message.level == "warning" {
+                            warnings += 1;
+                        }
+                        rendered.extend(message.rendered.clone());
+                    }
+                    _ => {}
+                }
+                diagnostics.extend(Diagnostic::from_message(msg, root_path));
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        spinner.tick(&status_msg);
+    }
+
+    let status = child.wait()?;
+    let stderr = stderr.join().unwrap_or_default();
+    spinner.clear();
+
+    if !status.success() {
+        for diagnostic in rendered.iter() {
+            eprint!("{diagnostic}");
+        }
+        // any other errors from `cargo`, i.e. if a dependency can't be found
+        for line in stderr.lines() {
+            let word = line.trim_start().split(' ').next().unwrap_or_default();
+            if !PROGRESS_LINES.contains(&word) {
+                eprintln!("{line}");
+            }
+        }
+    } else if warnings > 0 {
+        eprintln!(
+            "{}",
+            format!("({warnings} warning(s) in the build are hidden by `--clean-output`)").dimmed()
+        );
+    }
+
+    Ok(BuildOutput {
+        command: command_line(build),
+        status,
+        executables,
+        diagnostics,
+    })
+}
@@ -1,39 +1,519 @@
 use crate::*;
 
+use std::env;
 use std::fs;
-use std::fs::create_dir_all;
-use std::path::PathBuf;
-
-/// Get the *local cache* directory where we store the *last replay* info
-pub fn last_replay_path() -> PathBuf {
-    home::cargo_home()
-        .unwrap()
-        .join(BINARY_NAME)
-        .join("replays")
-        .join(SETTINGS_TOML)
-}
-
-/// Return the *last replay* info, which includes *example name* along with
-/// the *arguments* it was last called with.
-pub fn get_last_replay() -> Result<ReplayConfig> {
-    let bytes_data = fs::read(last_replay_path())?;
-    Ok(toml::from_slice(&bytes_data)?)
-}
-
-/// Save the *last replay* info, which includes *example name* along with
-/// the *arguments* it was last called with.
-pub fn save_last_replay<'a>(name: &'a str, args: &[&'a str]) -> Result<()> {
-    let example = LastRun {
-        name: name.to_string(),
-        arguments: args.iter().map(|&s| s.to_owned()).collect(),
+use std::fs::{create_dir_all, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Once;
+
+use colored::Colorize;
+use toml::value::{Table, Value};
+
+/// Current version of the config in the *local cache* files
+const CACHE_VERSION: u32 = 1;
+
+/// Get the *state* directory where `rx` stores its *local cache*, such
+/// as the replay info and run history.
+///
+/// This is the first of the following which is set:
+///   - `$RX_HOME`
+///   - `$XDG_STATE_HOME/rx`
+///   - `~/.local/state/rx` (or `%LOCALAPPDATA%\rx` on Windows)
+///   - `<temp dir>/rx`, in case the home directory can't be determined
+///
+/// Any *replay* info from a previous version of `rx`, which was stored
+/// under `~/.cargo/rx`, is migrated on first use -- which is only checked
+/// once per process.
+///
+/// There's no *config* directory (i.e. under `$XDG_CONFIG_HOME`), as `rx`
+/// is only configured per project, under `[package.metadata.rx]`.
+pub fn cache_dir() -> PathBuf {
+    static MIGRATE: Once = Once::new();
+
+    let dir = state_dir();
+    MIGRATE.call_once(|| migrate_legacy_cache(&dir));
+    dir
+}
+
+/// Return the *state* directory for `rx`; see [`cache_dir`].
+fn state_dir() -> PathBuf {
+    let var = |key: &str| {
+        env::var_os(key)
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
     };
-    let config = ReplayConfig { last_run: example };
 
-    let cache = last_replay_path();
-    create_dir_all(cache.parent().unwrap())?;
+    if let Some(dir) = var(RX_HOME) {
+        return dir;
+    }
+
+    if let Some(dir) = var(XDG_STATE_HOME).filter(|dir| dir.is_absolute()) {
+        return dir.join(BINARY_NAME);
+    }
+
+    #[cfg(target_family = "windows")]
+    let dir = var("LOCALAPPDATA");
+
+    #[cfg(not(target_family = "windows"))]
+    let dir = home::home_dir().map(|home| home.join(".local").join("state"));
+
+    dir.unwrap_or_else(env::temp_dir).join(BINARY_NAME)
+}
+
+/// Move the *replay* info from the legacy location under `~/.cargo/rx`
+/// to the *state* directory `dir`, if it's not there already.
+///
+/// Any errors are ignored, as the replay info can always be re-created.
+fn migrate_legacy_cache(dir: &Path) {
+    let legacy_dir = match home::cargo_home() {
+        Ok(cargo_home) => cargo_home.join(BINARY_NAME),
+        Err(_) => return,
+    };
+
+    let legacy = legacy_dir.join("replays").join(SETTINGS_TOML);
+    let path = dir.join("replays").join(SETTINGS_TOML);
+
+    if legacy == path || path.exists() || !legacy.is_file() {
+        return;
+    }
 
-    let data = toml::to_vec(&config)?;
-    fs::write(cache, data)?;
+    if create_dir_all(path.parent().unwrap()).is_ok() && fs::copy(&legacy, &path).is_ok() {
+        let _ = fs::remove_file(&legacy);
+        // only removes the folders if they're empty
+        let _ = fs::remove_dir(legacy.parent().unwrap());
+        let _ = fs::remove_dir(&legacy_dir);
+    }
+}
+
+/// Return a unique *key* for the Cargo project at `root`, which is the
+/// folder name along with a hash of the full path.
+fn project_key(root: &Path) -> String {
+    // FNV-1a, as it's stable across Rust versions, unlike `DefaultHasher`
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in root.to_string_lossy().bytes() {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+
+    let name = root
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    format!("{name}-{hash:016x}")
+}
+
+/// Get the *local cache* directory for the Cargo project at `root`
+pub fn project_cache_dir(root: &Path) -> PathBuf {
+    cache_dir().join("projects").join(project_key(root))
+}
+
+/// Return the *local cache* directories for all Cargo projects.
+pub fn project_cache_dirs() -> Result<Vec<PathBuf>> {
+    let entries = match fs::read_dir(cache_dir().join("projects")) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut dirs = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            dirs.push(path);
+        }
+    }
+    dirs.sort();
+
+    Ok(dirs)
+}
+
+/// Get the *local cache* file where we store the *last replay* info for
+/// the Cargo project at `root`
+pub fn last_replay_path(root: &Path) -> PathBuf {
+    project_cache_dir(root).join(SETTINGS_TOML)
+}
+
+/// Get the *local cache* file where we store the *last replay* info across
+/// all projects
+pub fn global_replay_path() -> PathBuf {
+    cache_dir().join("replays").join(SETTINGS_TOML)
+}
+
+/// Migrations for the config in a *local cache* file, where the migration
+/// at index `i` upgrades the config from version `i` to `i + 1`.
+const MIGRATIONS: &[fn(&mut Table)] = &[migrate_v0];
+
+/// Version `0` is the config from before it was versioned, which has the
+/// same layout as version `1`.
+fn migrate_v0(_config: &mut Table) {}
+
+/// Read the config from a *local cache* file at `path`, upgrading it to
+/// the current version if needed.
+///
+/// If the file doesn't exist, a default config is returned. If the file
+/// can't be read or parsed, a warning is shown and a default config is
+/// returned instead, so that a corrupt cache doesn't abort the run.
+fn read_config(path: &Path) -> ReplayConfig {
+    let config = match fs::read_to_string(path) {
+        Ok(data) => parse_config(&data).and_then(|config| match config {
+            Some(config) => Ok(config),
+            // the config from a newer version is read as far as possible
+            None => Ok(toml::from_str(&data)?),
+        }),
+        Err(e) if e.kind() == ErrorKind::NotFound => return ReplayConfig::default(),
+        Err(e) => Err(e.into()),
+    };
+
+    config.unwrap_or_else(|e| {
+        eprintln!(
+            "{} failed to read `{}`: {e}",
+            "warning:".yellow().bold(),
+            path.display()
+        );
+        ReplayConfig::default()
+    })
+}
+
+/// Read the config from a *local cache* file at `path`, in order to update
+/// it -- which should only be called while holding the lock on the file.
+///
+/// Unlike [`read_config`], an error reading the file is returned, so that
+/// the file is not overwritten. If the file can't be parsed, it's backed up
+/// and a default config is returned instead.
+///
+/// # Returns
+/// Returns `None` if the file was written by a newer version of `rx`, in
+/// which case it should be left as-is.
+fn read_config_for_update(path: &Path) -> Result<Option<ReplayConfig>> {
+    let data = match fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Some(ReplayConfig::default())),
+        Err(e) => return Err(format!("failed to read `{}`: {e}", path.display()).into()),
+    };
+
+    match parse_config(&data) {
+        Ok(config) => Ok(config),
+        Err(e) => {
+            let backup = with_suffix(path, ".bak");
+            fs::rename(path, &backup)
+                .map_err(|e| format!("failed to back up `{}`: {e}", path.display()))?;
+
+            eprintln!(
+                "{} failed to parse `{}`: {e}",
+                "warning:".yellow().bold(),
+                path.display()
+            );
+            eprintln!(
+                "{} the file was backed up to `{}`, and reset",
+                "note:".cyan().bold(),
+                backup.display()
+            );
+
+            Ok(Some(ReplayConfig::default()))
+        }
+    }
+}
+
+/// Parse the config in a *local cache* file, upgrading it to the current
+/// version if needed.
+///
+/// # Returns
+/// Returns `None` if the config is from a newer version of `rx`, which is
+/// checked before it's deserialized, as its layout may have changed; see
+/// [`update_config`].
+fn parse_config(data: &str) -> Result<Option<ReplayConfig>> {
+    let mut config: Table = toml::from_str(data)?;
+
+    let version = config
+        .get("version")
+        .and_then(Value::as_integer)
+        .unwrap_or_default();
+
+    if version > CACHE_VERSION.into() {
+        return Ok(None);
+    }
+
+    for migrate in MIGRATIONS.iter().skip(version.max(0) as usize) {
+        migrate(&mut config);
+    }
+    config.insert("version".to_owned(), Value::Integer(CACHE_VERSION.into()));
+
+    Ok(Some(Value::Table(config).try_into()?))
+}
+
+/// Write the `config` to a *local cache* file at `path`, overwriting any
+/// existing data.
+///
+/// The config is written to a temp file first, which is then renamed, so
+/// that the file is never left partially written.
+fn write_config(path: &Path, config: &ReplayConfig) -> Result<()> {
+    create_dir_all(path.parent().unwrap())?;
+
+    let data = toml::to_vec(config)?;
+
+    let temp = with_suffix(path, &format!(".{}.tmp", process::id()));
+    fs::write(&temp, data)?;
+
+    if let Err(e) = fs::rename(&temp, path) {
+        let _ = fs::remove_file(&temp);
+        return Err(e.into());
+    }
 
     Ok(())
 }
+
+/// Update the config in a *local cache* file at `path` with `f`, while
+/// holding a lock on the file -- so that any concurrent updates from
+/// other `rx` processes are not lost.
+fn update_config<F: FnOnce(&mut ReplayConfig)>(path: &Path, f: F) -> Result<()> {
+    let _lock = FileLock::acquire(path)?;
+
+    // a newer version of `rx` may store fields which would be dropped
+    let mut config = match read_config_for_update(path)? {
+        Some(config) => config,
+        None => {
+            eprintln!(
+                "{} `{}` was written by a newer version of `{BINARY_NAME}`, so it's not updated",
+                "warning:".yellow().bold(),
+                path.display()
+            );
+            return Ok(());
+        }
+    };
+
+    f(&mut config);
+    config.version = CACHE_VERSION;
+
+    write_config(path, &config)
+}
+
+/// Update the config for the Cargo project at `root` with `f`; see
+/// [`update_config`].
+fn update_project_config<F: FnOnce(&mut ReplayConfig)>(root: &Path, f: F) -> Result<()> {
+    update_config(&last_replay_path(root), |config| {
+        config.root = Some(root.to_path_buf());
+        f(config);
+    })
+}
+
+/// Return the *replay* config for the Cargo project at `root`, which
+/// includes the *example name* last run, along with the *arguments* it
+/// was last called with.
+pub fn get_last_replay(root: &Path) -> ReplayConfig {
+    read_config(&last_replay_path(root))
+}
+
+/// Return the *replay* config in a project's *local cache* directory `dir`.
+pub fn get_project_config(dir: &Path) -> ReplayConfig {
+    read_config(&dir.join(SETTINGS_TOML))
+}
+
+/// Return the *replay* config across all projects.
+pub fn get_global_config() -> ReplayConfig {
+    read_config(&global_replay_path())
+}
+
+/// Clear the info on the example *last run* across all projects; if a
+/// project `root` is passed in, only clear it if it was run in that project.
+pub fn clear_global_last_run(root: Option<&Path>) -> Result<()> {
+    update_config(&global_replay_path(), |config| {
+        if root.is_none() || config.last_run.root.as_deref() == root {
+            config.last_run = LastRun::default();
+        }
+    })
+}
+
+/// Return the info on the example *last run* in the Cargo project at `root`.
+///
+/// If no example has been run in the project yet, the *legacy* replay info
+/// (from before it was stored per project) is returned instead, if any.
+pub fn get_last_run(root: &Path) -> Result<LastRun> {
+    let config = get_last_replay(root);
+    if !config.last_run.name.is_empty() {
+        return Ok(config.last_run);
+    }
+
+    match read_config(&global_replay_path()).last_run {
+        last_run if last_run.root.is_none() && !last_run.name.is_empty() => Ok(last_run),
+        _ => Err(format!(
+            "no example has been run in this project yet; use \
+            `{BINARY_NAME} --replay --global` to replay the last example run in any project"
+        )
+        .into()),
+    }
+}
+
+/// Return the info on the example *last run* across all projects, which
+/// includes the `root` path of its Cargo project -- unless it's *legacy*
+/// replay info.
+pub fn get_global_last_run() -> Result<LastRun> {
+    let last_run = read_config(&global_replay_path()).last_run;
+
+    if last_run.name.is_empty() {
+        return Err("no example has been run yet".into());
+    }
+
+    Ok(last_run)
+}
+
+/// Save the *last replay* info for the Cargo project at `root`, which
+/// includes *example names* along with the *arguments* (and *stdin*) they
+/// were last called with.
+///
+/// This info is also saved *globally*, so it can be replayed from any
+/// project with `--replay --global`.
+pub fn save_last_replay(root: &Path, example: LastRun) -> Result<()> {
+    update_config(&global_replay_path(), |config| {
+        config.last_run = LastRun {
+            root: Some(root.to_path_buf()),
+            ..example.clone()
+        };
+    })?;
+
+    update_project_config(root, |config| {
+        config.last_run = example;
+    })
+}
+
+/// Save the *log level* settings selected for an example `name` in the
+/// Cargo project at `root`, so they can be re-used on later runs of the
+/// example.
+pub fn save_log_settings(root: &Path, name: &str, settings: &LogSettings) -> Result<()> {
+    update_project_config(root, |config| {
+        config
+            .log_settings
+            .insert(name.to_owned(), settings.to_owned());
+    })
+}
+
+/// Get the *local cache* file where we store the *run history* for the
+/// Cargo project at `root`
+pub fn history_path(root: &Path) -> PathBuf {
+    project_cache_dir(root).join(HISTORY_JSONL)
+}
+
+/// Return the entries in the *run history* for the Cargo project at `root`,
+/// from oldest to newest.
+///
+/// Any entries which can't be parsed are skipped.
+pub fn get_history(root: &Path) -> Result<Vec<HistoryEntry>> {
+    read_history(&history_path(root))
+}
+
+/// Return the entries in the *run history* file at `path`, from oldest to
+/// newest.
+pub fn read_history(path: &Path) -> Result<Vec<HistoryEntry>> {
+    let data = match fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    Ok(data
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Append an `entry` to the *run history* for the Cargo project at `root`.
+pub fn append_history(root: &Path, entry: &HistoryEntry) -> Result<()> {
+    let history = history_path(root);
+    create_dir_all(history.parent().unwrap())?;
+
+    let _lock = FileLock::acquire(&history)?;
+
+    let mut line = serde_json::to_string(entry)?;
+    line.push('\n');
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(history)?
+        .write_all(line.as_bytes())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Return the path to a `settings.toml` file in a new temp folder for
+    /// the test `name`.
+    fn temp_config(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("rx-test-{name}-{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        create_dir_all(&dir).unwrap();
+        dir.join(SETTINGS_TOML)
+    }
+
+    #[test]
+    fn test_parse_config_migrates_old_version() {
+        let data = "[last_run]\nname = \"demo\"\narguments = [\"--verbose\"]\n";
+
+        let config = parse_config(data).unwrap().unwrap();
+        assert_eq!(config.version, CACHE_VERSION);
+        assert_eq!(config.last_run.name, "demo");
+        assert_eq!(config.last_run.arguments, ["--verbose"]);
+    }
+
+    #[test]
+    fn test_update_config_migrates_old_version() {
+        let path = temp_config("migrate");
+        fs::write(&path, "[last_run]\nname = \"demo\"\narguments = []\n").unwrap();
+
+        update_config(&path, |config| {
+            config.last_run.arguments.push("--verbose".to_owned())
+        })
+        .unwrap();
+
+        let config = parse_config(&fs::read_to_string(&path).unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(config.version, CACHE_VERSION);
+        assert_eq!(config.last_run.name, "demo");
+        assert_eq!(config.last_run.arguments, ["--verbose"]);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_update_config_leaves_newer_version() {
+        let path = temp_config("newer");
+        // the layout has changed in the newer version
+        let data = format!("version = {}\nlast_run = \"demo\"\n", CACHE_VERSION + 1);
+        fs::write(&path, &data).unwrap();
+
+        assert!(parse_config(&data).unwrap().is_none());
+        update_config(&path, |config| config.last_run.name = "other".to_owned()).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), data);
+        assert!(!with_suffix(&path, ".bak").exists());
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_update_config_backs_up_corrupt_file() {
+        let path = temp_config("corrupt");
+        let data = "[last_run\nname = ";
+        fs::write(&path, data).unwrap();
+
+        update_config(&path, |config| config.last_run.name = "demo".to_owned()).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(with_suffix(&path, ".bak")).unwrap(),
+            data
+        );
+        let config = parse_config(&fs::read_to_string(&path).unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(config.version, CACHE_VERSION);
+        assert_eq!(config.last_run.name, "demo");
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,254 @@
+//! Logic for the `rx cache` subcommands, to inspect or clear the local cache.
+//!
+use crate::*;
+
+use std::ffi::OsStr;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use serde_json::json;
+
+/// Process an `rx cache` subcommand.
+///
+/// # Arguments
+///
+/// * `command` - the subcommand to process.
+/// * `root` - the base path to the Cargo directory of the current project,
+///   if any.
+pub(crate) fn process_cache_command(command: CacheCommand, root: Option<&Path>) -> Result<()> {
+    match command {
+        CacheCommand::Show { all: true, json } => {
+            let dirs = project_cache_dirs()?;
+            if json {
+                let projects: Vec<_> = dirs.iter().map(|dir| project_json(dir)).collect();
+                let output = json!({
+                    "path": cache_dir(),
+                    "last_run": get_global_config().last_run,
+                    "projects": projects,
+                });
+                println!("{}", serde_json::to_string_pretty(&output)?);
+            } else if dirs.is_empty() {
+                println!("The local cache is empty.");
+            } else {
+                for (i, dir) in dirs.iter().enumerate() {
+                    if i > 0 {
+                        println!();
+                    }
+                    print_project(dir);
+                }
+            }
+        }
+        CacheCommand::Show { all: false, json } => {
+            let dir = project_cache_dir(current_root(root)?);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&project_json(&dir))?);
+            } else {
+                print_project(&dir);
+            }
+        }
+        CacheCommand::Path { all: true } => println!("{}", cache_dir().display()),
+        CacheCommand::Path { all: false } => {
+            println!("{}", project_cache_dir(current_root(root)?).display())
+        }
+        CacheCommand::Clear { all: true } => {
+            clear_all()?;
+            println!("Cleared the local cache for all projects.");
+        }
+        CacheCommand::Clear { all: false } => {
+            let root = current_root(root)?;
+            remove_dir(&project_cache_dir(root))?;
+            clear_global_last_run(Some(root))?;
+            println!("Cleared the local cache for `{}`.", root.display());
+        }
+        CacheCommand::Prune => {
+            let (projects, files) = prune()?;
+            println!(
+                "Removed the local cache for {projects} project(s), and {files} leftover file(s)."
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Return the `root` path of the current project, or an error if `rx` is
+/// not run from within a project.
+fn current_root(root: Option<&Path>) -> Result<&Path> {
+    root.ok_or_else(|| {
+        "not in a Cargo project; pass `--all` to use the local cache for all projects".into()
+    })
+}
+
+/// Return the *size* of the file at `path` in bytes, or `0` if it doesn't
+/// exist.
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map(|m| m.len()).unwrap_or_default()
+}
+
+/// Return the details on a project's *local cache* directory `dir`, as JSON.
+fn project_json(dir: &Path) -> serde_json::Value {
+    let config = get_project_config(dir);
+    let history = dir.join(HISTORY_JSONL);
+
+    json!({
+        "root": config.root,
+        "path": dir,
+        "last_run": config.last_run,
+        "log_settings": config.log_settings,
+        "history": {
+            "entries": read_history(&history).map(|h| h.len()).unwrap_or_default(),
+            "bytes": file_size(&history),
+        },
+    })
+}
+
+/// Print the details on a project's *local cache* directory `dir`.
+fn print_project(dir: &Path) {
+    let label = |name: &str| format!("{:<14}", name).cyan().bold();
+
+    let config = get_project_config(dir);
+    let history = dir.join(HISTORY_JSONL);
+
+    if let Some(ref root) = config.root {
+        println!("{}{}", label("Project:"), root.display());
+    }
+    println!("{}{}", label("Path:"), dir.display());
+
+    let last_run = &config.last_run;
+    if last_run.name.is_empty() {
+        println!("{}{}", label("Last run:"), "none".dimmed());
+    } else {
+        let mut summary = last_run.examples().join(", ");
+        for arg in last_run.cargo_args.iter().chain(last_run.arguments.iter()) {
+            summary.push(' ');
+            summary.push_str(&OsStr::new(arg).shell_escape());
+        }
+        println!("{}{summary}", label("Last run:"));
+    }
+
+    for (name, settings) in config.log_settings.iter() {
+        let env = [
+            (RUST_LOG, &settings.rust_log),
+            (RUST_BACKTRACE, &settings.rust_backtrace),
+        ]
+        .iter()
+        .filter_map(|(key, value)| value.as_ref().map(|v| format!("{key}={v}")))
+        .collect::<Vec<_>>()
+        .join(" ");
+        println!("{}{name}: {env}", label("Log settings:"));
+    }
+
+    let entries = read_history(&history).map(|h| h.len()).unwrap_or_default();
+    println!(
+        "{}{entries} entries ({} bytes)",
+        label("History:"),
+        file_size(&history)
+    );
+}
+
+/// Remove the directory at `dir`, if it exists.
+fn remove_dir(dir: &Path) -> Result<()> {
+    match fs::remove_dir_all(dir) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Remove the file at `path`, if it exists.
+fn remove_file(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Remove the *local cache* for all projects, along with the *last run*
+/// across all projects.
+///
+/// Only the files which `rx` owns are removed, as the cache directory can
+/// be set to any folder via `$RX_HOME`; each folder is then removed only
+/// if it's left empty.
+fn clear_all() -> Result<()> {
+    for dir in project_cache_dirs()? {
+        remove_dir(&dir)?;
+    }
+
+    let replay = global_replay_path();
+    remove_file(&replay)?;
+    remove_file(&with_suffix(&replay, ".lock"))?;
+
+    let replays_dir = replay.parent().unwrap();
+    remove_leftover_files(replays_dir)?;
+
+    let cache_dir = cache_dir();
+    for dir in [replays_dir, &cache_dir.join("projects"), &cache_dir] {
+        // only removes the folders if they're empty
+        let _ = fs::remove_dir(dir);
+    }
+
+    Ok(())
+}
+
+/// Remove the *local cache* for any projects which no longer exist, along
+/// with any leftover temp and backup files.
+///
+/// # Returns
+/// Returns the number of projects, and the number of leftover files, which
+/// were removed.
+fn prune() -> Result<(usize, usize)> {
+    let mut projects = 0;
+    let mut files = 0;
+
+    for dir in project_cache_dirs()? {
+        // only projects which recorded their root path can be checked
+        if let Some(root) = get_project_config(&dir).root {
+            if !root.join(CARGO_TOML).is_file() {
+                remove_dir(&dir)?;
+                projects += 1;
+                continue;
+            }
+        }
+        files += remove_leftover_files(&dir)?;
+    }
+
+    if let Some(dir) = global_replay_path().parent() {
+        files += remove_leftover_files(dir)?;
+    }
+
+    // the last run across all projects is stale if its project is gone
+    if let Some(root) = get_global_config().last_run.root {
+        if !root.join(CARGO_TOML).is_file() {
+            clear_global_last_run(Some(&root))?;
+        }
+    }
+
+    Ok((projects, files))
+}
+
+/// Remove any leftover temp (`.tmp`) and backup (`.bak`) files in `dir`,
+/// and return the number of files removed.
+fn remove_leftover_files(dir: &Path) -> Result<usize> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e.into()),
+    };
+
+    let leftover: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            matches!(
+                path.extension().and_then(OsStr::to_str),
+                Some("tmp") | Some("bak")
+            )
+        })
+        .collect();
+
+    for path in leftover.iter() {
+        fs::remove_file(path)?;
+    }
+
+    Ok(leftover.len())
+}
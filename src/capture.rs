@@ -0,0 +1,103 @@
+//! Logic for *capturing* the output of an example process, while still
+//! passing it through to the terminal.
+//!
+use crate::*;
+
+use std::io::{self, Read, Write};
+use std::process::{Child, ExitStatus};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// The *output* captured from an example process.
+#[derive(Debug)]
+pub(crate) struct CapturedOutput {
+    /// Exit status of the process
+    pub status: ExitStatus,
+
+    /// True if the process was killed after it timed out
+    pub timed_out: bool,
+
+    /// What the process wrote to *stdout*
+    pub stdout: Vec<u8>,
+
+    /// What the process wrote to *stderr*
+    pub stderr: Vec<u8>,
+}
+
+impl CapturedOutput {
+    /// Create a `CapturedOutput` for a process which exited with `status`,
+    /// with no output captured.
+    pub fn empty(status: ExitStatus) -> Self {
+        Self {
+            status,
+            timed_out: false,
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+        }
+    }
+}
+
+/// Copy all output from `reader` to `writer` in a separate thread, and
+/// return the output once `reader` is closed -- or only the last `limit`
+/// bytes of it, if a limit is set.
+fn tee<R, W>(mut reader: R, mut writer: W, limit: Option<usize>) -> JoinHandle<Vec<u8>>
+where
+    R: Read + Send + 'static,
+    W: Write + Send + 'static,
+{
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        let mut buf = [0; 8192];
+
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    let _ = writer.write_all(&buf[..n]);
+                    let _ = writer.flush();
+                    output.extend_from_slice(&buf[..n]);
+                    // only trim once in a while, rather than on each read
+                    if let Some(limit) = limit.filter(|&limit| output.len() > 2 * limit) {
+                        output.drain(..output.len() - limit);
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            }
+        }
+
+        if let Some(limit) = limit.filter(|&limit| output.len() > limit) {
+            output.drain(..output.len() - limit);
+        }
+
+        output
+    })
+}
+
+/// Wait on an example process `child`, which was spawned with its output
+/// piped, while passing its output through to the terminal.
+///
+/// If a `limit` is set, only the last `limit` bytes of *stdout* and
+/// *stderr* are kept. If a `timeout` is set, the process is killed once it
+/// expires.
+pub(crate) fn wait_with_output(
+    mut child: Child,
+    limit: Option<usize>,
+    timeout: Option<Duration>,
+) -> Result<CapturedOutput> {
+    let stdout = child.stdout.take().map(|out| tee(out, io::stdout(), limit));
+    let stderr = child.stderr.take().map(|err| tee(err, io::stderr(), limit));
+
+    let (status, timed_out) = wait_with_timeout(&mut child, timeout)?;
+
+    let output = |handle: Option<JoinHandle<Vec<u8>>>| {
+        handle.and_then(|h| h.join().ok()).unwrap_or_default()
+    };
+
+    Ok(CapturedOutput {
+        status,
+        timed_out,
+        stdout: output(stdout),
+        stderr: output(stderr),
+    })
+}
@@ -0,0 +1,240 @@
+//! Extensions for `cargo_options::CommonOptions`
+//!
+use crate::*;
+
+use std::iter;
+
+use cargo_options::CommonOptions;
+use clap::Parser;
+
+#[doc(hidden)]
+pub trait CommonOptionsExt: Sized {
+    fn from_args(args: &[String]) -> Result<Self>;
+    fn to_args(&self) -> Vec<String>;
+    fn or(self, other: Self) -> Self;
+}
+
+impl CommonOptionsExt for CommonOptions {
+    /// Parse the options from *command-line arguments*, as returned by
+    /// [`to_args`](CommonOptionsExt::to_args).
+    fn from_args(args: &[String]) -> Result<Self> {
+        Self::try_parse_from(iter::once(BINARY_NAME).chain(args.iter().map(String::as_str)))
+            .map_err(|e| format!("failed to parse cargo options `{}`: {e}", args.join(" ")).into())
+    }
+
+    /// Return the *command-line arguments* which correspond to the options,
+    /// i.e. `["--release", "--features", "my-feature"]`.
+    fn to_args(&self) -> Vec<String> {
+        let mut args: Vec<String> = Vec::new();
+
+        let mut push = |flag: &str, value: Option<String>| {
+            args.push(flag.to_owned());
+            if let Some(value) = value {
+                args.push(value);
+            }
+        };
+
+        if self.quiet {
+            push("--quiet", None);
+        }
+        if let Some(jobs) = self.jobs {
+            push("--jobs", Some(jobs.to_string()));
+        }
+        if self.release {
+            push("--release", None);
+        }
+        if let Some(ref profile) = self.profile {
+            push("--profile", Some(profile.to_owned()));
+        }
+        if !self.features.is_empty() {
+            push("--features", Some(self.features.join(" ")));
+        }
+        if self.all_features {
+            push("--all-features", None);
+        }
+        if self.no_default_features {
+            push("--no-default-features", None);
+        }
+        for target in self.target.iter() {
+            push("--target", Some(target.to_owned()));
+        }
+        if let Some(ref target_dir) = self.target_dir {
+            push("--target-dir", Some(target_dir.display().to_string()));
+        }
+        if let Some(ref manifest_path) = self.manifest_path {
+            push("--manifest-path", Some(manifest_path.display().to_string()));
+        }
+        for fmt in self.message_format.iter() {
+            push("--message-format", Some(fmt.to_owned()));
+        }
+        if self.unit_graph {
+            push("--unit-graph", None);
+        }
+        if self.ignore_rust_version {
+            push("--ignore-rust-version", None);
+        }
+        for _ in 0..self.verbose {
+            push("--verbose", None);
+        }
+        if let Some(ref when) = self.color {
+            push("--color", Some(when.to_owned()));
+        }
+        if self.frozen {
+            push("--frozen", None);
+        }
+        if self.locked {
+            push("--locked", None);
+        }
+        if self.offline {
+            push("--offline", None);
+        }
+        for cfg in self.config.iter() {
+            push("--config", Some(cfg.to_owned()));
+        }
+        for flag in self.unstable_flags.iter() {
+            push("-Z", Some(flag.to_owned()));
+        }
+
+        args
+    }
+
+    /// Return the options, falling back to the ones in `other` for any
+    /// which are not set -- i.e. explicit options take precedence over
+    /// the ones `other` was run with.
+    fn or(self, other: Self) -> Self {
+        fn or_vec<T>(v: Vec<T>, other: Vec<T>) -> Vec<T> {
+            if v.is_empty() {
+                other
+            } else {
+                v
+            }
+        }
+
+        // `--release` and `--profile` are two ways to select a profile
+        let has_profile = self.release || self.profile.is_some();
+
+        Self {
+            quiet: self.quiet || other.quiet,
+            jobs: self.jobs.or(other.jobs),
+            release: self.release || (!has_profile && other.release),
+            profile: if has_profile {
+                self.profile
+            } else {
+                other.profile
+            },
+            features: or_vec(self.features, other.features),
+            all_features: self.all_features || other.all_features,
+            no_default_features: self.no_default_features || other.no_default_features,
+            target: or_vec(self.target, other.target),
+            target_dir: self.target_dir.or(other.target_dir),
+            manifest_path: self.manifest_path.or(other.manifest_path),
+            message_format: or_vec(self.message_format, other.message_format),
+            unit_graph: self.unit_graph || other.unit_graph,
+            ignore_rust_version: self.ignore_rust_version || other.ignore_rust_version,
+            verbose: self.verbose.max(other.verbose),
+            color: self.color.or(other.color),
+            frozen: self.frozen || other.frozen,
+            locked: self.locked || other.locked,
+            offline: self.offline || other.offline,
+            config: or_vec(self.config, other.config),
+            unstable_flags: or_vec(self.unstable_flags, other.unstable_flags),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    fn options() -> CommonOptions {
+        CommonOptions {
+            quiet: true,
+            jobs: Some(4),
+            profile: Some("bench".to_owned()),
+            features: vec!["my-feature".to_owned()],
+            no_default_features: true,
+            target: vec!["x86_64-unknown-linux-gnu".to_owned()],
+            target_dir: Some(PathBuf::from("target/rx")),
+            message_format: vec!["short".to_owned()],
+            verbose: 2,
+            color: Some("never".to_owned()),
+            locked: true,
+            config: vec!["build.jobs=2".to_owned()],
+            unstable_flags: vec!["unstable-options".to_owned()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_to_args() {
+        assert!(CommonOptions::default().to_args().is_empty());
+        assert_eq!(
+            options().to_args(),
+            [
+                "--quiet",
+                "--jobs",
+                "4",
+                "--profile",
+                "bench",
+                "--features",
+                "my-feature",
+                "--no-default-features",
+                "--target",
+                "x86_64-unknown-linux-gnu",
+                "--target-dir",
+                "target/rx",
+                "--message-format",
+                "short",
+                "--verbose",
+                "--verbose",
+                "--color",
+                "never",
+                "--locked",
+                "--config",
+                "build.jobs=2",
+                "-Z",
+                "unstable-options",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_args_round_trip() {
+        let args = options().to_args();
+        let parsed = <CommonOptions as CommonOptionsExt>::from_args(&args).unwrap();
+
+        assert_eq!(parsed.to_args(), args);
+        assert_eq!(parsed.jobs, Some(4));
+        assert_eq!(parsed.profile.as_deref(), Some("bench"));
+        assert_eq!(parsed.features, ["my-feature"]);
+        assert_eq!(parsed.verbose, 2);
+        assert!(parsed.quiet && parsed.no_default_features && parsed.locked);
+        assert!(!parsed.release && !parsed.offline);
+    }
+
+    #[test]
+    fn test_from_args_invalid() {
+        assert!(
+            <CommonOptions as CommonOptionsExt>::from_args(&["--no-such-flag".to_owned()]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_or() {
+        let explicit = CommonOptions {
+            release: true,
+            features: vec!["other".to_owned()],
+            ..Default::default()
+        };
+        let merged = explicit.or(options());
+
+        // `--release` takes precedence over the previous `--profile`
+        assert!(merged.release);
+        assert_eq!(merged.profile, None);
+        assert_eq!(merged.features, ["other"]);
+        assert_eq!(merged.jobs, Some(4));
+        assert_eq!(merged.verbose, 2);
+    }
+}
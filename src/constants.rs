@@ -16,8 +16,14 @@ pub const RUST_FILE_EXT: &str = "rs";
 pub const CARGO_TOML: &str = "Cargo.toml";
 /// Name of the `main.rs` file in Cargo projects
 pub const MAIN_RS: &str = "main.rs";
+/// Name of the `lib.rs` file in Cargo projects
+pub const LIB_RS: &str = "lib.rs";
 /// Name of the `settings.toml` file for local project cache
 pub const SETTINGS_TOML: &str = "settings.toml";
+/// Name of the `history.jsonl` file for the local *run history*
+pub const HISTORY_JSONL: &str = "history.jsonl";
+/// Name of the file which a run is written to, for editing via `--edit`
+pub const EDIT_TOML: &str = "edit-replay.toml";
 
 /// Commands
 
@@ -25,6 +31,47 @@ pub const SETTINGS_TOML: &str = "settings.toml";
 pub const CARGO_CMD: &str = "cargo";
 /// The `fzf` (fuzzy finder) command
 pub const FZF_CMD: &str = "fzf";
+/// The `cargo miri` subcommand, which runs an example in the Miri interpreter
+pub const MIRI_SUBCOMMAND: &str = "miri";
+/// Wrapper command to debug an example with `gdb`, via `--gdb`
+pub const GDB_WRAPPER: &str = "gdb --args";
+/// Wrapper command to debug an example with `lldb`, via `--lldb`
+pub const LLDB_WRAPPER: &str = "lldb --";
+/// Wrapper command to check an example for memory errors, via `--valgrind`
+pub const VALGRIND_WRAPPER: &str = "valgrind";
+/// Wrapper command to trace the system calls of an example, via `--strace`
+pub const STRACE_WRAPPER: &str = "strace -f";
+/// Wrapper command to profile an example with `perf`, via `--perf`
+pub const PERF_WRAPPER: &str = "perf record --";
+
+// Environment variables
+
+/// Env variable which controls the log level, for example with `env_logger`
+pub const RUST_LOG: &str = "RUST_LOG";
+/// Env variable which controls whether a *backtrace* is displayed on panic
+pub const RUST_BACKTRACE: &str = "RUST_BACKTRACE";
+/// Env variable for the flags to pass to Miri, via `--miri`
+pub const MIRIFLAGS: &str = "MIRIFLAGS";
+/// Env variable for the user's preferred (visual) editor
+pub const VISUAL: &str = "VISUAL";
+/// Env variable for the user's preferred editor
+pub const EDITOR: &str = "EDITOR";
+/// Env variable which overrides the directory where `rx` stores its *local cache*
+pub const RX_HOME: &str = "RX_HOME";
+/// Env variable for the base directory of user-specific *state* files
+pub const XDG_STATE_HOME: &str = "XDG_STATE_HOME";
+/// Env variable for the search path of *dynamic libraries*, which `cargo run`
+/// extends with the target directory
+#[cfg(target_os = "windows")]
+pub const DYLIB_PATH: &str = "PATH";
+/// Env variable for the search path of *dynamic libraries*, which `cargo run`
+/// extends with the target directory
+#[cfg(target_os = "macos")]
+pub const DYLIB_PATH: &str = "DYLD_FALLBACK_LIBRARY_PATH";
+/// Env variable for the search path of *dynamic libraries*, which `cargo run`
+/// extends with the target directory
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+pub const DYLIB_PATH: &str = "LD_LIBRARY_PATH";
 
 /// Newline character as bytes
 pub const NL: &u8 = &b'\n';
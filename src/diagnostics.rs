@@ -0,0 +1,208 @@
+//! Logic for jumping to the *compiler errors* of an example which failed to
+//! build, by selecting one in the fuzzy finder and opening its location in
+//! the editor.
+//!
+use crate::*;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use colored::Colorize;
+
+/// Number of lines of source code to show around a diagnostic, in the
+/// preview window
+const PREVIEW_CONTEXT: usize = 5;
+
+/// A *diagnostic* emitted by the compiler, which points to a location in
+/// the source code.
+#[derive(Debug)]
+pub(crate) struct Diagnostic {
+    /// The *level* of the diagnostic, for ex. `error` or `warning`
+    pub level: String,
+
+    /// The main message of the diagnostic
+    pub message: String,
+
+    /// Absolute path to the source file
+    pub file: PathBuf,
+
+    /// Line in the source file, starting at 1
+    pub line: usize,
+
+    /// Column in the source file, starting at 1
+    pub column: usize,
+
+    /// The diagnostic as rendered by the compiler, if any
+    pub rendered: Option<String>,
+}
+
+impl Diagnostic {
+    /// Create a `Diagnostic` from a Cargo message `msg`, for a build of the
+    /// project at `root` -- if it's a compiler message which points to a
+    /// location in the source code.
+    pub fn from_message(msg: CargoMessage, root: &Path) -> Option<Self> {
+        if msg.reason != "compiler-message" {
+            return None;
+        }
+        let message = msg.message?;
+        let span = message.primary_span()?;
+
+        // the path is relative to the workspace root, which is either the
+        // project root or the folder of a nested crate
+        let file = if span.file_name.is_absolute() {
+            span.file_name.clone()
+        } else {
+            let package_dir = msg.manifest_path.as_deref().and_then(Path::parent);
+            std::iter::once(root)
+                .chain(package_dir)
+                .map(|dir| dir.join(&span.file_name))
+                .find(|path| path.is_file())?
+        };
+
+        Some(Self {
+            line: span.line_start,
+            column: span.column_start,
+            file,
+            level: message.level,
+            message: message.message,
+            rendered: message.rendered,
+        })
+    }
+
+    /// Return true if the diagnostic is an error, rather than a warning.
+    fn is_error(&self) -> bool {
+        // an ICE has a level of `error: internal compiler error`
+        self.level.starts_with("error")
+    }
+
+    /// Return the *location* of the diagnostic, i.e. `file:line:column`,
+    /// with the path relative to `root` if possible.
+    fn location(&self, root: &Path) -> String {
+        let file = self.file.strip_prefix(root).unwrap_or(&self.file);
+        format!("{}:{}:{}", file.display(), self.line, self.column)
+    }
+
+    /// Return the source code around the diagnostic, with its line
+    /// highlighted, for the preview window.
+    fn source_excerpt(&self) -> Option<String> {
+        let source = fs::read_to_string(&self.file).ok()?;
+
+        let first = self.line.saturating_sub(PREVIEW_CONTEXT).max(1);
+        let last = self.line + PREVIEW_CONTEXT;
+        let width = last.to_string().len();
+
+        let excerpt = source
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line))
+            .skip(first - 1)
+            .take(last + 1 - first)
+            .map(|(num, line)| match num == self.line {
+                true => format!("{} {line}\n", format!("{num:>width$} >").yellow().bold()),
+                false => format!("{} {line}\n", format!("{num:>width$} |").dimmed()),
+            })
+            .collect();
+
+        Some(excerpt)
+    }
+
+    /// Return the content for the *preview* window, which is the source
+    /// code around the diagnostic, followed by the rendered diagnostic.
+    fn preview(&self) -> String {
+        let mut preview = self.source_excerpt().unwrap_or_default();
+        if let Some(ref rendered) = self.rendered {
+            preview.push('\n');
+            preview.push_str(rendered);
+        }
+        preview
+    }
+}
+
+/// Offer to jump to one of the `diagnostics` for an example `name` which
+/// failed to build, by selecting it in the fuzzy finder and opening its
+/// location in the editor. The errors are listed before any warnings.
+///
+/// # Returns
+/// Returns true if the user wants to run the example again, once the
+/// editor is closed.
+pub(crate) fn jump_to_diagnostic(
+    root: &Path,
+    name: &str,
+    mut diagnostics: Vec<Diagnostic>,
+) -> Result<bool> {
+    diagnostics.sort_by_key(|d| !d.is_error());
+
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+    if errors == 0 {
+        return Ok(false);
+    }
+
+    eprintln!(
+        "{} example `{name}` failed to build with {errors} error(s) \
+        -- select one to open it in the editor",
+        "note:".cyan().bold(),
+    );
+
+    let items = diagnostics
+        .iter()
+        .map(|d| FinderItem {
+            text: format!("{}: {}  {}", d.level, d.message, d.location(root)),
+            preview: Some(d.preview()),
+        })
+        .collect();
+
+    let diagnostic = match select_items(items, "Diagnostics> ", false)?.first() {
+        Some(&i) => &diagnostics[i],
+        None => return Ok(false),
+    };
+
+    match editor() {
+        Some(editor) => open_at(&editor, diagnostic)?,
+        None => eprintln!(
+            "{} no editor is set via `${VISUAL}` or `${EDITOR}`; the error is at {}",
+            "warning:".yellow().bold(),
+            diagnostic.location(root),
+        ),
+    }
+
+    confirm(&format!("Run example `{name}` again?"), true)
+}
+
+/// Open the location of a `diagnostic` in the `editor`, and wait for the
+/// editor to be closed.
+fn open_at(editor: &str, diagnostic: &Diagnostic) -> Result<()> {
+    // The editor command can contain arguments, for ex. `code --wait`
+    let mut words = shellwords::split(editor)?;
+    let program = words.remove(0);
+
+    let file = diagnostic.file.display();
+    let (line, column) = (diagnostic.line, diagnostic.column);
+
+    let mut cmd = Command::new(&program);
+    cmd.args(words);
+
+    // most terminal editors accept a `+<line>` argument, but some only
+    // accept the line and column as part of the path
+    let name = Path::new(&program)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    match name.as_str() {
+        "code" | "code-insiders" | "codium" => {
+            cmd.arg("--goto").arg(format!("{file}:{line}:{column}"))
+        }
+        "subl" | "zed" | "hx" | "helix" => cmd.arg(format!("{file}:{line}:{column}")),
+        _ => cmd.arg(format!("+{line}")).arg(&diagnostic.file),
+    };
+
+    let status = cmd
+        .status()
+        .map_err(|e| format!("failed to open editor `{program}`: {e}"))?;
+
+    if !status.success() {
+        return Err(format!("editor `{program}` exited with {status}").into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+//! Logic for editing a run before it's replayed, via `--replay --edit`.
+//!
+use crate::*;
+
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+
+use colored::Colorize;
+
+/// Header for the TOML document which is opened in the editor
+const EDIT_HEADER: &str = "\
+# Edit the run below, then save and close the file to run it.
+# Paths should be absolute. Delete all lines to cancel the run.
+
+";
+
+/// Return the user's preferred *editor* command, if one is set via the
+/// `VISUAL` or `EDITOR` env variables.
+pub(crate) fn editor() -> Option<String> {
+    [VISUAL, EDITOR]
+        .iter()
+        .filter_map(|key| env::var(key).ok())
+        .find(|cmd| !cmd.trim().is_empty())
+}
+
+/// Open a `run` for editing, and return the modified run -- or `None` if
+/// the user cancelled it.
+///
+/// If an *editor* is set, the run is opened as a TOML document in the
+/// editor; otherwise, the user is prompted to edit just the arguments.
+pub(crate) fn edit_run(root: &Path, run: LastRun) -> Result<Option<LastRun>> {
+    match editor() {
+        Some(editor) => edit_in_editor(root, &editor, run),
+        None => {
+            let arguments = prompt_arguments(&run.arguments)?;
+            Ok(Some(LastRun { arguments, ..run }))
+        }
+    }
+}
+
+/// Prompt the user for arguments to pass to an example; if the user
+/// doesn't enter any, the `current` arguments are returned instead.
+///
+/// The arguments are returned with a leading `--`, so they can be passed
+/// directly to `cargo run`.
+pub(crate) fn prompt_arguments(current: &[String]) -> Result<Vec<String>> {
+    // Print label for input, along with the current arguments
+    print!("{} ", "Arguments:".cyan().bold());
+    let current_args = current
+        .iter()
+        .skip_while(|arg| *arg == "--")
+        .map(|arg| OsStr::new(arg).shell_escape())
+        .collect::<Vec<_>>();
+    if !current_args.is_empty() {
+        print!("{} ", format!("[{}]", current_args.join(" ")).dimmed());
+    }
+    std::io::stdout().flush()?;
+
+    // Read user input
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+    let line = line.trim_end();
+
+    if line.is_empty() && !current.is_empty() {
+        return Ok(current.to_vec());
+    }
+
+    // Split user-entered arguments, respecting quotes and other characters
+    let words = expand_response_files(shellwords::split(line)?)?;
+
+    let mut extra_args = Vec::with_capacity(words.len() + 1);
+    if !words.is_empty() {
+        extra_args.push("--".to_owned());
+        extra_args.extend(words);
+    }
+
+    Ok(extra_args)
+}
+
+/// Ask the user a yes-or-no `question`, and return the answer -- or the
+/// `default` answer if the user doesn't enter one.
+pub(crate) fn confirm(question: &str, default: bool) -> Result<bool> {
+    let choices = match default {
+        true => "[Y/n]",
+        false => "[y/N]",
+    };
+    print!("{} {} ", question.cyan().bold(), choices.dimmed());
+    std::io::stdout().flush()?;
+
+    let mut line = String::new();
+    if std::io::stdin().read_line(&mut line)? == 0 {
+        return Ok(false);
+    }
+
+    Ok(match line.trim().to_ascii_lowercase().as_str() {
+        "" => default,
+        answer => answer == "y" || answer == "yes",
+    })
+}
+
+/// Open a `run` as a TOML document in the `editor`, and return the
+/// modified run once the editor is closed.
+fn edit_in_editor(root: &Path, editor: &str, run: LastRun) -> Result<Option<LastRun>> {
+    let path = project_cache_dir(root).join(EDIT_TOML);
+    fs::create_dir_all(path.parent().unwrap())?;
+
+    let contents = format!("{EDIT_HEADER}{}", toml::to_string(&run)?);
+    fs::write(&path, contents)?;
+
+    // The editor command can contain arguments, for ex. `code --wait`
+    let mut words = shellwords::split(editor)?;
+    let program = words.remove(0);
+
+    let status = Command::new(&program)
+        .args(words)
+        .arg(&path)
+        .status()
+        .map_err(|e| format!("failed to open editor `{program}`: {e}"))?;
+
+    if !status.success() {
+        return Err(format!("editor `{program}` exited with {status}").into());
+    }
+
+    let contents = fs::read_to_string(&path)?;
+    fs::remove_file(&path)?;
+
+    if contents.lines().all(|line| {
+        let line = line.trim();
+        line.is_empty() || line.starts_with('#')
+    }) {
+        return Ok(None);
+    }
+
+    let run: LastRun =
+        toml::from_str(&contents).map_err(|e| format!("failed to parse the edited run: {e}"))?;
+
+    if run.name.is_empty() {
+        return Err("the edited run is missing an example `name`".into());
+    }
+
+    Ok(Some(run))
+}
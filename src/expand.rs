@@ -0,0 +1,193 @@
+//! Expansion of *response files* (`@file`) and *placeholders* such as
+//! `{root}` or `${ENV}` in the arguments passed to an example.
+//!
+use crate::*;
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+/// Prefix which marks an argument as a *response file*, i.e. `@args.txt`
+const RESPONSE_FILE_PREFIX: char = '@';
+
+/// Expand any *response files* in `args`, such as `@args.txt`, into the
+/// arguments contained in the file.
+///
+/// Each line in a response file is split into arguments, respecting quotes
+/// and other characters; blank lines and lines starting with `#` are
+/// skipped. Use `@@` to pass in an argument that starts with a literal `@`.
+pub(crate) fn expand_response_files(args: Vec<String>) -> Result<Vec<String>> {
+    let mut expanded = Vec::with_capacity(args.len());
+
+    for arg in args {
+        let file = match arg.strip_prefix(RESPONSE_FILE_PREFIX) {
+            Some(file) if !file.is_empty() => file,
+            _ => {
+                expanded.push(arg);
+                continue;
+            }
+        };
+
+        // `@@value` is an escaped `@value`
+        if file.starts_with(RESPONSE_FILE_PREFIX) {
+            expanded.push(file.to_owned());
+            continue;
+        }
+
+        let contents = fs::read_to_string(file)
+            .map_err(|e| format!("failed to read response file `{file}`: {e}"))?;
+
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            expanded.extend(shellwords::split(line)?);
+        }
+    }
+
+    Ok(expanded)
+}
+
+/// Expand any *placeholders* in an argument `arg`.
+///
+/// The supported placeholders are:
+///   - `{name}`, where `name` is one of the keys in `vars`; for example
+///     `{root}`. Any other text in braces is left as-is.
+///   - `${NAME}`, which is replaced with the value of the env variable.
+///
+/// # Errors
+/// Raises an error if a referenced env variable is not set.
+pub(crate) fn expand_placeholders(arg: &str, vars: &[(&str, &Path)]) -> Result<String> {
+    let mut expanded = String::with_capacity(arg.len());
+    let mut rest = arg;
+
+    while let Some(start) = rest.find('{') {
+        let (before, after) = rest.split_at(start);
+
+        let end = match after.find('}') {
+            Some(end) => end,
+            None => break,
+        };
+
+        let key = &after[1..end];
+
+        // a nested `{`, so the current one can't be a placeholder
+        if key.contains('{') {
+            expanded.push_str(before);
+            expanded.push('{');
+            rest = &after[1..];
+            continue;
+        }
+
+        if let Some(before) = before.strip_suffix('$') {
+            let value = env::var(key)
+                .map_err(|e| format!("failed to expand `${{{key}}}` in arguments: {e}"))?;
+            expanded.push_str(before);
+            expanded.push_str(&value);
+        } else if let Some((_, value)) = vars.iter().find(|(name, _)| *name == key) {
+            expanded.push_str(before);
+            expanded.push_str(&value.to_string_lossy());
+        } else {
+            expanded.push_str(before);
+            expanded.push_str(&after[..=end]);
+        }
+
+        rest = &after[end + 1..];
+    }
+
+    expanded.push_str(rest);
+
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|&arg| arg.to_owned()).collect()
+    }
+
+    #[test]
+    fn test_expand_response_files() {
+        let file = env::temp_dir().join(format!("rx-test-args-{}.txt", std::process::id()));
+        fs::write(
+            &file,
+            "# a comment\n--name \"hello world\"\n\n  -v 'single quoted'  \n",
+        )
+        .unwrap();
+
+        let args = to_strings(&["first", &format!("@{}", file.display()), "last"]);
+        let expanded = expand_response_files(args);
+        fs::remove_file(&file).unwrap();
+
+        assert_eq!(
+            expanded.unwrap(),
+            [
+                "first",
+                "--name",
+                "hello world",
+                "-v",
+                "single quoted",
+                "last"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_response_files_escaped() {
+        let args = to_strings(&["@@literal", "@", "plain@value"]);
+        assert_eq!(
+            expand_response_files(args).unwrap(),
+            ["@literal", "@", "plain@value"]
+        );
+    }
+
+    #[test]
+    fn test_expand_response_files_missing() {
+        let args = to_strings(&["@/nonexistent/rx-args.txt"]);
+        assert!(expand_response_files(args).is_err());
+    }
+
+    #[test]
+    fn test_expand_placeholders() {
+        let vars = [
+            ("root", Path::new("/home/me/project")),
+            ("examples", Path::new("/home/me/project/examples")),
+        ];
+
+        assert_eq!(
+            expand_placeholders("{root}/data.txt", &vars).unwrap(),
+            "/home/me/project/data.txt"
+        );
+        assert_eq!(
+            expand_placeholders("--in={examples}/in --out={root}", &vars).unwrap(),
+            "--in=/home/me/project/examples/in --out=/home/me/project"
+        );
+        assert_eq!(
+            expand_placeholders("no placeholders", &vars).unwrap(),
+            "no placeholders"
+        );
+        // unknown keys, unclosed and nested braces are left as-is
+        assert_eq!(
+            expand_placeholders("{unknown}", &vars).unwrap(),
+            "{unknown}"
+        );
+        assert_eq!(expand_placeholders("{root", &vars).unwrap(), "{root");
+        assert_eq!(
+            expand_placeholders("{{root}}", &vars).unwrap(),
+            "{/home/me/project}"
+        );
+    }
+
+    #[test]
+    fn test_expand_placeholders_env() {
+        env::set_var("RX_TEST_EXPAND_VAR", "value");
+
+        assert_eq!(
+            expand_placeholders("a-${RX_TEST_EXPAND_VAR}-b", &[]).unwrap(),
+            "a-value-b"
+        );
+        assert!(expand_placeholders("${RX_TEST_UNSET_VAR}", &[]).is_err());
+    }
+}
@@ -0,0 +1,73 @@
+//! Logic for exporting a run via `--export`, and for running a shared
+//! *run file* via `--run-file`.
+//!
+use crate::*;
+
+use std::fs;
+use std::path::Path;
+
+/// Header for an exported *run file*
+const RUN_FILE_HEADER: &str = "\
+# Run file for `rx`; paths are relative to the project root.
+# Run it with `rx --run-file <file>`.
+
+";
+
+/// Print an exported run in the given `format`.
+///
+/// # Arguments
+///
+/// * `format` - Format to export the run in.
+/// * `root` - the base path to the Cargo directory.
+/// * `run` - the run to export, which is used for the `toml` format.
+/// * `commands` - the command to run each example from the project root,
+///   which is used for the other formats.
+pub(crate) fn print_export(
+    format: ExportFormat,
+    root: &Path,
+    run: &LastRun,
+    commands: &[String],
+) -> Result<()> {
+    match format {
+        ExportFormat::Sh => {
+            println!("#!/bin/sh");
+            println!("# Generated with `{BINARY_NAME} --export sh`; save it in the project root,");
+            println!("# as the examples are run from there.");
+            println!("set -e");
+            println!("cd \"$(dirname \"$0\")\"");
+            println!();
+            for cmd in commands {
+                println!("{cmd}");
+            }
+        }
+        ExportFormat::Cargo => {
+            for cmd in commands {
+                println!("{cmd}");
+            }
+        }
+        ExportFormat::Toml => {
+            let run = run.clone().strip_root(root);
+            print!("{RUN_FILE_HEADER}{}", toml::to_string(&run)?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Read a *run file* at `path`, as created with `--export toml`, and
+/// return the run it describes.
+///
+/// Any relative paths in the run file are resolved against the project `root`.
+pub(crate) fn read_run_file(path: &Path, root: &Path) -> Result<LastRun> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read run file `{}`: {e}", path.display()))?;
+
+    let run: LastRun = toml::from_str(&contents)
+        .map_err(|e| format!("failed to parse run file `{}`: {e}", path.display()))?;
+
+    if run.name.is_empty() {
+        return Err(format!("run file `{}` is missing an example `name`", path.display()).into());
+    }
+
+    Ok(run.join_root(root))
+}
@@ -0,0 +1,213 @@
+//! Platform-specific logic for the *fuzzy finder* -- which is [skim] on
+//! Mac/Linux, and [fzf] on Windows.
+//!
+//! [skim]: https://github.com/lotabout/skim
+//! [fzf]: https://github.com/junegunn/fzf
+//!
+use crate::*;
+pub(crate) use finder_impl::*;
+
+/// Represents a generic *item* which can be selected in the fuzzy finder.
+#[cfg_attr(target_family = "windows", allow(dead_code))]
+pub(crate) struct FinderItem {
+    /// Text to display (and search on) in the fuzzy finder
+    pub text: String,
+
+    /// Content to display in the *preview* window, if any
+    pub preview: Option<String>,
+}
+
+impl FinderItem {
+    /// Create a `FinderItem` with no *preview* content.
+    pub fn new<S: Into<String>>(text: S) -> Self {
+        Self {
+            text: text.into(),
+            preview: None,
+        }
+    }
+}
+
+#[cfg(target_family = "windows")]
+mod finder_impl {
+    use super::*;
+
+    use std::borrow::Cow;
+    use std::collections::BTreeMap;
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    /// Open the fuzzy finder with the *input* lines, and return the lines
+    /// which the user selected.
+    fn run_fzf(input: String, fzf_args: &[&str]) -> Result<String> {
+        let mut child = Command::new(FZF_CMD)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .args(fzf_args)
+            .spawn()
+            .expect("Failed to spawn child process");
+
+        // pipe stdin in to the `fzf` command
+        let mut stdin = child.stdin.take().expect("Failed to open stdin");
+        std::thread::spawn(move || {
+            stdin
+                .write_all(input.as_bytes())
+                .expect("Failed to write to stdin");
+        });
+
+        // get the output from running `fzf`
+        let output = child.wait_with_output().expect("Failed to read stdout");
+
+        Ok(String::from_utf8(output.stdout)?)
+    }
+
+    /// Prompt the user to select one or more examples to run.
+    pub(crate) fn select_examples(
+        example_files: &BTreeMap<Cow<'_, str>, ExampleFile>,
+    ) -> Result<Vec<String>> {
+        let example_names: String = example_files
+            .keys()
+            .map(|k| k.as_ref())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let output = run_fzf(example_names, &["-m"])?;
+
+        Ok(output.split_terminator('\n').map(str::to_owned).collect())
+    }
+
+    /// Prompt the user to select from a list of `items`, and return the
+    /// *indices* of the selected items -- which are empty if the user aborted.
+    pub(crate) fn select_items(
+        items: Vec<FinderItem>,
+        prompt: &str,
+        multi: bool,
+    ) -> Result<Vec<usize>> {
+        // prefix each line with its index, so we can map the selected
+        // lines back to the items.
+        let input = items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| format!("{i}\t{}", item.text))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut fzf_args = vec!["--delimiter", "\t", "--with-nth", "2..", "--prompt", prompt];
+        if multi {
+            fzf_args.push("-m");
+        }
+
+        let output = run_fzf(input, &fzf_args)?;
+
+        Ok(output
+            .split_terminator('\n')
+            .filter_map(|line| line.split('\t').next()?.parse().ok())
+            .collect())
+    }
+}
+
+#[cfg(not(target_family = "windows"))]
+mod finder_impl {
+    use super::*;
+
+    use std::borrow::Cow;
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    use skim::prelude::*;
+
+    /// Wraps a `FinderItem`, so we can keep track of its *index* in the
+    /// original list.
+    struct IndexedItem {
+        index: usize,
+        item: FinderItem,
+    }
+
+    impl SkimItem for IndexedItem {
+        fn text(&self) -> Cow<'_, str> {
+            Cow::Borrowed(&self.item.text)
+        }
+
+        fn preview(&self, _context: PreviewContext<'_>) -> ItemPreview {
+            match self.item.preview {
+                Some(ref preview) => ItemPreview::AnsiText(preview.to_owned()),
+                None => ItemPreview::Text(String::new()),
+            }
+        }
+    }
+
+    /// Run `skim` with the sent items, and return the items which the user
+    /// selected -- or `None` if the user aborted, by pressing a key such as
+    /// Esc, Backspace, or Delete.
+    fn run_skim(
+        options: &SkimOptions<'_>,
+        rx_item: SkimItemReceiver,
+    ) -> Option<Vec<Arc<dyn SkimItem>>> {
+        match Skim::run_with(options, Some(rx_item)) {
+            Some(out) if out.is_abort => None,
+            Some(out) => Some(out.selected_items),
+            None => Some(Vec::new()),
+        }
+    }
+
+    /// Prompt the user to select one or more examples to run.
+    pub(crate) fn select_examples(
+        example_files: &BTreeMap<Cow<'_, str>, ExampleFile>,
+    ) -> Result<Vec<String>> {
+        let options = SkimOptionsBuilder::default()
+            // .height(Some("50%"))
+            .preview_window(Some("right:70%"))
+            .multi(true)
+            .preview(Some("")) // preview should be specified to enable preview window
+            .build()
+            .unwrap();
+
+        let (tx_item, rx_item): (SkimItemSender, SkimItemReceiver) = unbounded();
+
+        for example in example_files.values() {
+            let _ = tx_item.send(Arc::new(ExampleFileItem {
+                file_stem: example.name.clone(),
+                file_path: example.path.clone(),
+            }));
+        }
+        drop(tx_item); // so that skim could know when to stop waiting for more items.
+
+        // Quit, if the user aborted
+        let selected = run_skim(&options, rx_item).unwrap_or_else(|| std::process::exit(0));
+
+        Ok(selected
+            .iter()
+            .map(|item| item.text().into_owned())
+            .collect())
+    }
+
+    /// Prompt the user to select from a list of `items`, and return the
+    /// *indices* of the selected items -- which are empty if the user aborted.
+    pub(crate) fn select_items(
+        items: Vec<FinderItem>,
+        prompt: &str,
+        multi: bool,
+    ) -> Result<Vec<usize>> {
+        let has_preview = items.iter().any(|item| item.preview.is_some());
+
+        let mut builder = SkimOptionsBuilder::default();
+        builder.prompt(Some(prompt)).multi(multi);
+        if has_preview {
+            builder.preview_window(Some("right:60%")).preview(Some(""));
+        }
+        let options = builder.build().unwrap();
+
+        let (tx_item, rx_item): (SkimItemSender, SkimItemReceiver) = unbounded();
+
+        for (index, item) in items.into_iter().enumerate() {
+            let _ = tx_item.send(Arc::new(IndexedItem { index, item }));
+        }
+        drop(tx_item); // so that skim could know when to stop waiting for more items.
+
+        Ok(run_skim(&options, rx_item)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|item| (**item).as_any().downcast_ref::<IndexedItem>())
+            .map(|item| item.index)
+            .collect())
+    }
+}
@@ -0,0 +1,61 @@
+//! Simple *glob* matching, for patterns such as `*.swp` or `test_?`.
+//!
+
+/// Check if the `text` matches a glob `pattern`, where `*` matches any
+/// sequence of characters (including none), and `?` matches any single
+/// character.
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    // index in `pattern` and `text` to resume from, on the last `*`
+    let mut star: Option<(usize, usize)> = None;
+    let (mut p, mut t) = (0, 0);
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                // let the last `*` match one more character
+                Some((star_p, star_t)) => {
+                    star = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.swp", ".main.rs.swp"));
+        assert!(glob_match("test_?", "test_1"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(glob_match("**", "anything"));
+        assert!(glob_match("exact", "exact"));
+        assert!(glob_match("é?", "éü"));
+
+        assert!(!glob_match("*.swp", "main.rs"));
+        assert!(!glob_match("test_?", "test_"));
+        assert!(!glob_match("test_?", "test_12"));
+        assert!(!glob_match("a*b*c", "aXbYbZ"));
+        assert!(!glob_match("", "text"));
+        assert!(!glob_match("exact", "Exact"));
+    }
+}
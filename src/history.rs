@@ -0,0 +1,163 @@
+//! Logic for browsing and replaying entries in the *run history*.
+//!
+use crate::*;
+
+use std::ffi::OsStr;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use colored::Colorize;
+
+/// Return the current time, in seconds since the Unix epoch.
+pub(crate) fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Return the entry `number` in the run history for the Cargo project at
+/// `root`, where `1` is the oldest entry.
+pub(crate) fn history_entry(root: &Path, number: usize) -> Result<HistoryEntry> {
+    let mut history = get_history(root)?;
+    let len = history.len();
+
+    if number == 0 || number > len {
+        return Err(format!("no entry `{number}` in the run history ({len} entries)").into());
+    }
+
+    Ok(history.swap_remove(number - 1))
+}
+
+/// Return the most recent entry in the run history which ran an example `name`.
+pub(crate) fn last_history_entry_for(root: &Path, name: &str) -> Result<HistoryEntry> {
+    get_history(root)?
+        .into_iter()
+        .rev()
+        .find(|entry| entry.run.examples().iter().any(|n| n == name))
+        .ok_or_else(|| format!("no entry for example `{name}` in the run history").into())
+}
+
+/// Prompt the user to select an entry from the run history, which is
+/// displayed with the most recent entries first.
+pub(crate) fn select_history_entry(root: &Path) -> Result<Option<HistoryEntry>> {
+    let mut history = get_history(root)?;
+
+    if history.is_empty() {
+        return Err("the run history is empty".into());
+    }
+
+    let now = unix_timestamp();
+
+    let items = history
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(i, entry)| FinderItem {
+            text: format!(
+                "{:>4}  {:>8}  {}",
+                i + 1,
+                format_elapsed(now.saturating_sub(entry.timestamp)),
+                summary(entry)
+            ),
+            preview: Some(details(entry)),
+        })
+        .collect();
+
+    let selected = select_items(items, "History> ", false)?;
+
+    // the items were displayed in reverse order
+    Ok(selected
+        .first()
+        .map(|&i| history.len() - 1 - i)
+        .map(|i| history.swap_remove(i)))
+}
+
+/// Return a one-line *summary* of a history `entry`, i.e. the examples and
+/// arguments they were run with.
+fn summary(entry: &HistoryEntry) -> String {
+    let mut summary = entry.run.examples().join(", ");
+
+    for arg in entry.run.arguments.iter() {
+        summary.push(' ');
+        summary.push_str(&OsStr::new(arg).shell_escape());
+    }
+
+    if entry.results.iter().any(|r| !r.success()) {
+        summary.push_str("  (failed)");
+    }
+
+    summary
+}
+
+/// Return the *details* on a history `entry`, for the preview window.
+fn details(entry: &HistoryEntry) -> String {
+    let label = |name: &str| format!("{:<11}", name).cyan().bold();
+
+    let mut lines = vec![String::new()];
+
+    for result in entry.results.iter() {
+        let status = match result.exit_code {
+            _ if result.timed_out => "timed out".red(),
+            Some(0) if result.output_mismatch => "output mismatch".red(),
+            Some(0) => "exit 0".green(),
+            Some(code) => format!("exit {code}").red(),
+            None => "killed".red(),
+        };
+        lines.push(format!(
+            "{}{}  {}  {:.2}s",
+            label("Example:"),
+            result.name.bold(),
+            status,
+            result.duration
+        ));
+        for (key, value) in entry.run.env.get(&result.name).into_iter().flatten() {
+            lines.push(format!("{}{key}={value}", label("")));
+        }
+    }
+
+    if !entry.run.arguments.is_empty() {
+        let args = entry
+            .run
+            .arguments
+            .iter()
+            .map(|arg| OsStr::new(arg).shell_escape())
+            .collect::<Vec<_>>()
+            .join(" ");
+        lines.push(format!("{}{args}", label("Arguments:")));
+    }
+
+    if !entry.run.cargo_args.is_empty() {
+        let args = entry
+            .run
+            .cargo_args
+            .iter()
+            .map(|arg| OsStr::new(arg).shell_escape())
+            .collect::<Vec<_>>()
+            .join(" ");
+        lines.push(format!("{}{args}", label("Cargo:")));
+    }
+
+    match entry.run.stdin() {
+        Some(StdinSource::File(path)) => {
+            lines.push(format!("{}< {}", label("Stdin:"), path.display()));
+        }
+        Some(StdinSource::Text(text)) => {
+            lines.push(format!("{}<<< {text:?}", label("Stdin:")));
+        }
+        None => {}
+    }
+
+    lines.join("\n")
+}
+
+/// Format a duration in `secs` as a human-readable *elapsed time*, for
+/// ex. `5m ago`.
+fn format_elapsed(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{secs}s ago"),
+        60..=3599 => format!("{}m ago", secs / 60),
+        3600..=86399 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
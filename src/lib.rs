@@ -83,33 +83,86 @@
 //! [`README.md`]: https://github.com/rnag/cargo-rx
 //!
 
+mod build;
 mod cache;
+mod cache_cmd;
+mod capture;
+mod commonoptionsext;
 mod constants;
+mod diagnostics;
+mod edit;
+mod expand;
+mod export;
+mod finder;
+mod glob;
+mod history;
+mod lock;
+mod logging;
 mod models;
 // noinspection SpellCheckingInspection
 mod osstringext;
 mod pathbufext;
+mod process;
+mod report;
 mod run_ext;
 mod run_impl;
+mod snapshot;
 mod types;
+mod watch;
 
+pub(crate) use build::*;
 use cache::*;
+pub(crate) use cache_cmd::*;
+pub(crate) use capture::*;
+pub use commonoptionsext::*;
 pub use constants::*;
+pub(crate) use diagnostics::*;
+pub(crate) use edit::*;
+pub(crate) use expand::*;
+pub(crate) use export::*;
+pub(crate) use finder::*;
+pub(crate) use glob::*;
+pub(crate) use history::*;
+pub(crate) use lock::*;
+pub(crate) use logging::*;
 pub use models::*;
 pub use osstringext::*;
 pub use pathbufext::*;
+pub(crate) use process::*;
+pub(crate) use report::*;
 pub use run_ext::*;
 pub(crate) use run_impl::*;
+pub(crate) use snapshot::*;
 pub use types::*;
+pub(crate) use watch::*;
 
 /// Processes an input to *select or run* an **example** in a [Cargo] project.
 ///
+/// # Returns
+/// Returns the *exit code* for `rx`, which is the exit code of the first
+/// example which failed -- or `0` if all examples ran successfully.
+///
 /// [Cargo]: http://doc.crates.io/
-pub fn process_input(args: Args) -> Result<()> {
+pub fn process_input(args: Args) -> Result<i32> {
     #[cfg(target_family = "windows")]
     patch_colored_for_windows();
 
-    let p = Paths::resolve()?;
+    if let Some(SubCommand::Cache { command }) = args.command {
+        // the `rx cache` subcommands don't need to be run in a project
+        let p = Paths::resolve().ok();
+        process_cache_command(command, p.as_ref().map(|p| p.root_path.as_path()))?;
+        return Ok(0);
+    }
+
+    let root = match args.replay && args.global {
+        true => get_global_last_run()?.root,
+        false => None,
+    };
+
+    let p = match root {
+        Some(root) => Paths::resolve_from(root)?,
+        None => Paths::resolve()?,
+    };
 
     let files = p.example_files()?;
 
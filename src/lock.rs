@@ -0,0 +1,111 @@
+//! Platform-specific logic for *advisory locking* of the files in the
+//! local cache, so that `rx` runs in parallel terminals don't clobber
+//! each other's changes.
+//!
+use crate::*;
+pub(crate) use lock_impl::*;
+
+use std::ffi::OsString;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// An exclusive lock on a file in the local cache, which is released once
+/// the `FileLock` is dropped.
+///
+/// The lock is held on a separate `<file>.lock` file, so that the file
+/// itself can be replaced atomically while the lock is held.
+pub(crate) struct FileLock {
+    /// Open handle to the lock file; closing it releases the lock
+    _file: File,
+}
+
+impl FileLock {
+    /// Acquire an exclusive lock on the file at `path`, waiting for any
+    /// other `rx` process which holds the lock to release it.
+    pub fn acquire(path: &Path) -> Result<Self> {
+        let lock_path = with_suffix(path, ".lock");
+        std::fs::create_dir_all(lock_path.parent().unwrap())?;
+
+        let file = lock_file(&lock_path)
+            .map_err(|e| format!("failed to lock `{}`: {e}", path.display()))?;
+
+        Ok(Self { _file: file })
+    }
+}
+
+/// Return the `path` with a `suffix` appended to the file name, i.e.
+/// `settings.toml.lock`.
+pub(crate) fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name: OsString = path.file_name().unwrap_or_default().to_owned();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+#[cfg(not(target_family = "windows"))]
+mod lock_impl {
+    use super::*;
+
+    use std::fs::OpenOptions;
+    use std::io;
+    use std::os::unix::io::AsRawFd;
+
+    /// Open the lock file at `path`, and wait on an exclusive `flock` on it.
+    pub(crate) fn lock_file(path: &Path) -> io::Result<File> {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path)?;
+
+        loop {
+            if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } == 0 {
+                return Ok(file);
+            }
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        }
+    }
+}
+
+#[cfg(target_family = "windows")]
+mod lock_impl {
+    use super::*;
+
+    use std::fs::OpenOptions;
+    use std::io;
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::thread::sleep;
+    use std::time::{Duration, Instant};
+
+    /// Error code for when a file is opened by another process
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+
+    /// How long to wait on another process to release the lock
+    const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Open the lock file at `path` with no sharing, which prevents any
+    /// other process from opening it until the handle is closed.
+    pub(crate) fn lock_file(path: &Path) -> io::Result<File> {
+        let start = Instant::now();
+
+        loop {
+            match OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .share_mode(0)
+                .open(path)
+            {
+                Err(e)
+                    if e.raw_os_error() == Some(ERROR_SHARING_VIOLATION)
+                        && start.elapsed() < LOCK_TIMEOUT =>
+                {
+                    sleep(Duration::from_millis(50));
+                }
+                result => return result,
+            }
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! Logic for the interactive *log level* picker, which sets the `RUST_LOG`
+//! (and optionally `RUST_BACKTRACE`) env variables when running an example.
+//!
+use crate::*;
+
+use std::fs;
+use std::path::Path;
+
+/// Log levels which can be selected, from most to least verbose
+const LOG_LEVELS: [&str; 6] = ["trace", "debug", "info", "warn", "error", "off"];
+
+/// Values which can be selected for `RUST_BACKTRACE`
+const BACKTRACE_VALUES: [&str; 2] = ["1", "full"];
+
+/// Prompt the user to select the *log levels* to run an example `name`
+/// with -- either globally, or for the example and each module in the
+/// crate's module tree.
+pub(crate) fn select_log_settings(dir: &Paths, name: &str) -> Result<LogSettings> {
+    // an example is compiled as its own crate, so the log target is
+    // simply the example name.
+    let mut targets = vec![name.replace('-', "_")];
+
+    for target in crate_module_targets(dir) {
+        if !targets.contains(&target) {
+            targets.push(target);
+        }
+    }
+
+    let mut choices: Vec<String> = LOG_LEVELS.iter().map(|&level| level.to_owned()).collect();
+
+    for target in targets.iter() {
+        for level in LOG_LEVELS {
+            choices.push(format!("{target}={level}"));
+        }
+    }
+
+    for value in BACKTRACE_VALUES {
+        choices.push(format!("{RUST_BACKTRACE}={value}"));
+    }
+
+    let items = choices.iter().map(FinderItem::new).collect();
+    let prompt = format!("{RUST_LOG}> ");
+
+    let mut directives = Vec::new();
+    let mut settings = LogSettings::default();
+
+    for i in select_items(items, &prompt, true)? {
+        let choice = choices[i].as_str();
+
+        match choice
+            .strip_prefix(RUST_BACKTRACE)
+            .and_then(|value| value.strip_prefix('='))
+        {
+            Some(value) => settings.rust_backtrace = Some(value.to_owned()),
+            None => directives.push(choice),
+        }
+    }
+
+    if !directives.is_empty() {
+        settings.rust_log = Some(directives.join(","));
+    }
+
+    Ok(settings)
+}
+
+/// Return the *log targets* for a crate and its modules -- for example,
+/// `my_crate` and `my_crate::utils` -- by following the `mod` declarations
+/// in the crate's `lib.rs` (or `main.rs`) file.
+fn crate_module_targets(dir: &Paths) -> Vec<String> {
+    let manifest = &dir.manifest;
+    let lib = manifest.lib.as_ref();
+
+    let crate_name = match (lib.and_then(|l| l.name.as_ref()), manifest.package.as_ref()) {
+        (Some(name), _) => name.to_owned(),
+        (None, Some(package)) => package.name.replace('-', "_"),
+        (None, None) => return Vec::new(),
+    };
+
+    let root_file = match lib.and_then(|l| l.path.as_ref()) {
+        Some(path) => dir.root_path.join(path),
+        None => {
+            let src_dir = dir.root_path.join("src");
+            let lib_rs = src_dir.join(LIB_RS);
+
+            if lib_rs.is_file() {
+                lib_rs
+            } else {
+                src_dir.join(MAIN_RS)
+            }
+        }
+    };
+
+    if !root_file.is_file() {
+        return Vec::new();
+    }
+
+    let mut targets = vec![crate_name.clone()];
+    collect_modules(&root_file, true, &crate_name, &mut targets);
+
+    targets
+}
+
+/// Recursively collect the *module paths* declared in a Rust source `file`.
+///
+/// # Arguments
+///
+/// * `file` - path to the Rust source file.
+/// * `is_mod_root` - true if the file is a `lib.rs`, `main.rs`, or `mod.rs`
+///   file, in which case sub-modules are located in the same folder.
+/// * `prefix` - the module path of the file, for ex. `my_crate::utils`.
+/// * `targets` - the module paths collected so far.
+fn collect_modules(file: &Path, is_mod_root: bool, prefix: &str, targets: &mut Vec<String>) {
+    let contents = match fs::read_to_string(file) {
+        Ok(contents) => contents,
+        Err(_) => return,
+    };
+
+    let parent = file.parent().unwrap();
+    let mod_dir = if is_mod_root {
+        parent.to_path_buf()
+    } else {
+        parent.join(file.file_stem().unwrap())
+    };
+
+    for name in contents.lines().filter_map(parse_mod_decl) {
+        let target = format!("{prefix}::{name}");
+
+        let name_rs = mod_dir.join(format!("{name}.{RUST_FILE_EXT}"));
+        let mod_rs = mod_dir.join(name).join("mod.rs");
+
+        targets.push(target.clone());
+
+        if name_rs.is_file() {
+            collect_modules(&name_rs, false, &target, targets);
+        } else if mod_rs.is_file() {
+            collect_modules(&mod_rs, true, &target, targets);
+        }
+    }
+}
+
+/// Parse a *module declaration* such as `pub(crate) mod utils;` from a
+/// `line`, and return the module name.
+///
+/// Note that *inline* modules, i.e. `mod tests { ... }`, are skipped.
+fn parse_mod_decl(line: &str) -> Option<&str> {
+    let mut line = line.trim();
+
+    if let Some(rest) = line.strip_prefix("pub") {
+        line = match rest.trim_start().strip_prefix('(') {
+            Some(rest) => rest.split_once(')')?.1,
+            None => rest,
+        }
+        .trim_start();
+    }
+
+    let name = line.strip_prefix("mod ")?.strip_suffix(';')?.trim();
+
+    Some(name.trim_start_matches("r#"))
+}
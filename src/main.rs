@@ -14,7 +14,11 @@ fn main() -> Result<()> {
         println!("Arguments: {:#?}", args);
     }
 
-    process_input(args)?;
+    let code = process_input(args)?;
+
+    if code != 0 {
+        std::process::exit(code);
+    }
 
     Ok(())
 }
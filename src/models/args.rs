@@ -1,29 +1,374 @@
-use crate::BINARY_NAME;
+use crate::{parse_duration, parse_size, BINARY_NAME};
 
-use clap::Parser;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use clap::{ArgEnum, Parser, Subcommand};
 
 /// A simple, modern Example Runner - and fuzzy finder - for Cargo projects.
 #[derive(Parser, Clone, Debug, Default)]
 #[clap(bin_name = BINARY_NAME)]
 #[clap(setting = clap::AppSettings::DeriveDisplayOrder, after_help = "Run `cargo help run` for more detailed information.")]
 #[clap(version)]
+#[clap(args_conflicts_with_subcommands = true)]
+#[clap(group(
+    clap::ArgGroup::new("wrapper")
+        .conflicts_with_all(&["export", "dry-run", "watch", "build-only"])
+))]
 pub struct Args {
     /// Base options for `cargo run --example`
     #[clap(flatten)]
     pub cargo: cargo_options::CommonOptions,
 
+    /// Subcommand to run, if any
+    #[clap(subcommand)]
+    pub command: Option<SubCommand>,
+
     /// Example script name
     pub name: Option<String>,
 
-    /// Do not prompt for parameters and only use information entered previously
+    /// Example script name, i.e. for an example which has the same name as
+    /// a subcommand, such as `history`
+    #[clap(long = "example", value_name = "NAME", conflicts_with = "name")]
+    pub example: Option<String>,
+
+    /// Run every example in the project, as a smoke test, and print a
+    /// pass/fail summary at the end
+    #[clap(
+        short,
+        long,
+        conflicts_with_all = &["name", "example", "replay", "run-file", "watch", "input-args"]
+    )]
+    pub all: bool,
+
+    /// With `--all`, only run the examples whose names match a glob pattern
+    #[clap(long, value_name = "GLOB", requires = "all")]
+    pub filter: Option<String>,
+
+    /// With `--all`, only build the examples rather than running them
+    #[clap(long, requires = "all", conflicts_with = "export")]
+    pub build_only: bool,
+
+    /// Do not prompt for parameters and only use information entered previously;
+    /// if an example name is passed, replay the last run of that example
     #[clap(short = 'R', long)]
     pub replay: bool,
 
+    /// With `--replay`, replay the last example run in *any* project,
+    /// rather than the last one run in the current project
+    #[clap(long, requires = "replay")]
+    pub global: bool,
+
+    /// With `--replay`, edit the run before replaying it -- in `$EDITOR`
+    /// as a TOML document if it's set, or else at the arguments prompt
+    #[clap(long, requires = "replay")]
+    pub edit: bool,
+
     /// True to prompt for arguments to the selected example script
     #[clap(short, long, short_alias = 'p', alias = "prompt-args")]
     pub input_args: bool,
 
+    /// Prompt for the log level (`RUST_LOG`) to run the example with; the
+    /// selection is remembered, and re-used on later runs of the example
+    #[clap(long)]
+    pub log: bool,
+
+    /// File to pipe in to the example's stdin
+    #[clap(long, value_name = "FILE", parse(from_os_str))]
+    pub stdin: Option<PathBuf>,
+
+    /// Text to pipe in to the example's stdin
+    #[clap(long, value_name = "TEXT", conflicts_with = "stdin")]
+    pub stdin_text: Option<String>,
+
+    /// Working directory to run the example in (cargo is still invoked
+    /// from the project root)
+    #[clap(long, value_name = "DIR", parse(from_os_str))]
+    pub cwd: Option<PathBuf>,
+
+    /// Run the example in its own directory, i.e. the folder of a
+    /// multi-file example or crate
+    #[clap(long, conflicts_with = "cwd")]
+    pub cwd_example: bool,
+
+    /// Watch the example's files, and rerun it whenever they change
+    #[clap(short, long, conflicts_with = "export")]
+    pub watch: bool,
+
+    /// Glob pattern for file or folder names to ignore in `--watch` mode,
+    /// in addition to `target`, `.git`, and editor swap files
+    #[clap(
+        long,
+        value_name = "GLOB",
+        multiple_occurrences = true,
+        requires = "watch"
+    )]
+    pub watch_ignore: Vec<String>,
+
+    /// Show a progress spinner in place of the build output, and only print
+    /// the compiler errors and warnings if the build fails
+    #[clap(long)]
+    pub clean_output: bool,
+
+    /// Build all examples up front in a single `cargo build`, and then run
+    /// their executables directly
+    #[clap(long, conflicts_with_all = &["watch", "export", "build-only"])]
+    pub prebuild: bool,
+
+    /// Keep running the remaining examples if one fails, and print a
+    /// summary at the end
+    #[clap(long)]
+    pub keep_going: bool,
+
+    /// Kill the example (and any processes it spawns) if it runs for
+    /// longer than this, i.e. `30s` or `2m`
+    #[clap(
+        long,
+        value_name = "DURATION",
+        parse(try_from_str = parse_duration),
+        conflicts_with = "watch"
+    )]
+    pub timeout: Option<Duration>,
+
+    /// Limit the memory of the example process, i.e. `512M` or `2G`
+    /// (Linux only)
+    #[clap(long, value_name = "SIZE", parse(try_from_str = parse_size))]
+    pub max_memory: Option<u64>,
+
+    /// Limit the CPU time of the example process, i.e. `10s` (Linux only)
+    #[clap(long, value_name = "DURATION", parse(try_from_str = parse_duration))]
+    pub max_cpu_time: Option<Duration>,
+
+    /// Run the executable of each example under a wrapper command, i.e.
+    /// `"valgrind --leak-check=full"`
+    #[clap(long, value_name = "CMD", group = "wrapper")]
+    pub wrap: Option<String>,
+
+    /// Debug the example with `gdb`; same as `--wrap "gdb --args"`
+    #[clap(long, group = "wrapper")]
+    pub gdb: bool,
+
+    /// Debug the example with `lldb`; same as `--wrap "lldb --"`
+    #[clap(long, group = "wrapper")]
+    pub lldb: bool,
+
+    /// Check the example for memory errors with `valgrind`
+    #[clap(long, group = "wrapper")]
+    pub valgrind: bool,
+
+    /// Trace the system calls of the example with `strace -f`
+    #[clap(long, group = "wrapper")]
+    pub strace: bool,
+
+    /// Profile the example with `perf record`
+    #[clap(long, group = "wrapper")]
+    pub perf: bool,
+
+    /// Run the example in the Miri interpreter, via `cargo +nightly miri
+    /// run`, to check for undefined behavior; any `MIRIFLAGS` are passed
+    /// through
+    #[clap(
+        long,
+        conflicts_with_all = &[
+            "wrapper", "prebuild", "clean-output", "max-memory", "max-cpu-time",
+            "check-output", "watch", "build-only", "cwd", "cwd-example",
+        ]
+    )]
+    pub miri: bool,
+
+    /// Print the run in the given format, rather than running it
+    #[clap(long, arg_enum, value_name = "FORMAT")]
+    pub export: Option<ExportFormat>,
+
+    /// Print the command to run each example (along with any env variables
+    /// and *stdin*), rather than running it
+    #[clap(
+        long,
+        conflicts_with_all = &["export", "watch", "build-only", "prebuild", "check-output", "report"]
+    )]
+    pub dry_run: bool,
+
+    /// Run the examples described in a run file, as created with `--export toml`
+    #[clap(
+        long,
+        value_name = "FILE",
+        parse(from_os_str),
+        conflicts_with = "replay"
+    )]
+    pub run_file: Option<PathBuf>,
+
+    /// Compare the output of each example against its snapshot files, i.e.
+    /// `examples/<name>.stdout` and `examples/<name>.stderr`
+    #[clap(long, conflicts_with_all = &["export", "watch", "build-only"])]
+    pub check_output: bool,
+
+    /// With `--check-output`, update the snapshot files with the output
+    #[clap(long, requires = "check-output")]
+    pub bless: bool,
+
+    /// With `--check-output`, replace any matches for a regex pattern in
+    /// the output with `[REDACTED]`; can be passed multiple times
+    #[clap(
+        long,
+        value_name = "REGEX",
+        multiple_occurrences = true,
+        requires = "check-output"
+    )]
+    pub redact: Vec<String>,
+
+    /// Write a report of the run to a file, as `junit=<path>` or
+    /// `json=<path>`; can be passed multiple times
+    #[clap(
+        long,
+        value_name = "FORMAT=PATH",
+        multiple_occurrences = true,
+        conflicts_with_all = &["export", "watch", "build-only"]
+    )]
+    pub report: Vec<ReportSpec>,
+
     /// Extra arguments to pass to the Cargo example script
     #[clap(raw = true)]
     pub args: Vec<String>,
 }
+
+/// Subcommands for `rx`
+#[derive(Subcommand, Clone, Debug)]
+pub enum SubCommand {
+    /// Browse the run history in the fuzzy finder, and re-run the selected entry
+    History,
+
+    /// Re-run an entry from the run history
+    Replay {
+        /// Number of the entry, as displayed by `rx history`
+        number: usize,
+    },
+
+    /// Inspect or clear the local cache, i.e. the replay info and run history
+    Cache {
+        /// The `rx cache` subcommand to run
+        #[clap(subcommand)]
+        command: CacheCommand,
+    },
+}
+
+/// Subcommands for `rx cache`
+#[derive(Subcommand, Clone, Debug)]
+pub enum CacheCommand {
+    /// Show what is stored in the local cache for the current project
+    Show {
+        /// Show the local cache for all projects
+        #[clap(long)]
+        all: bool,
+
+        /// Print the output as JSON
+        #[clap(long)]
+        json: bool,
+    },
+
+    /// Print the path to the local cache for the current project
+    Path {
+        /// Print the path to the local cache for all projects
+        #[clap(long)]
+        all: bool,
+    },
+
+    /// Clear the local cache for the current project
+    Clear {
+        /// Clear the local cache for all projects
+        #[clap(long)]
+        all: bool,
+    },
+
+    /// Remove the local cache for any projects which no longer exist, and
+    /// any leftover temp and backup files
+    Prune,
+}
+
+/// Formats to export a run in, via `--export`
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// A shell script which runs the examples
+    Sh,
+    /// The `cargo` command to run each example
+    Cargo,
+    /// A run file, which can be shared and run with `--run-file`
+    Toml,
+}
+
+/// Formats to write a report of a run in, via `--report`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// A JUnit XML report, as understood by most CI systems
+    Junit,
+    /// A JSON report
+    Json,
+}
+
+/// A report to write for a run, passed in as `--report <format>=<path>`
+#[derive(Clone, Debug)]
+pub struct ReportSpec {
+    /// Format of the report
+    pub format: ReportFormat,
+    /// Path to the file to write the report to
+    pub path: PathBuf,
+}
+
+impl FromStr for ReportSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (format, path) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected `<format>=<path>`, got `{s}`"))?;
+
+        let format = match format {
+            "junit" => ReportFormat::Junit,
+            "json" => ReportFormat::Json,
+            _ => {
+                return Err(format!(
+                    "unknown report format `{format}`; expected `junit` or `json`"
+                ))
+            }
+        };
+
+        if path.is_empty() {
+            return Err("the path to the report file is empty".to_owned());
+        }
+
+        Ok(Self {
+            format,
+            path: PathBuf::from(path),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_spec_from_str() {
+        let spec: ReportSpec = "junit=target/report.xml".parse().unwrap();
+        assert_eq!(spec.format, ReportFormat::Junit);
+        assert_eq!(spec.path, PathBuf::from("target/report.xml"));
+
+        // only the first `=` separates the format from the path
+        let spec: ReportSpec = "json=out=1.json".parse().unwrap();
+        assert_eq!(spec.format, ReportFormat::Json);
+        assert_eq!(spec.path, PathBuf::from("out=1.json"));
+    }
+
+    #[test]
+    fn test_report_spec_from_str_invalid() {
+        for s in [
+            "junit",
+            "xml=report.xml",
+            "JSON=report.json",
+            "json=",
+            "=report.xml",
+        ] {
+            assert!(s.parse::<ReportSpec>().is_err(), "`{s}` should be invalid");
+        }
+    }
+}
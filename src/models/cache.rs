@@ -1,18 +1,209 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
 use serde::{Deserialize, Serialize};
 
+use crate::StdinSource;
+
 /// Config which contains *replay* details on the *most recent* run example.
 #[derive(Deserialize, Serialize, Default)]
 pub struct ReplayConfig {
+    /// Version of the config, which is used to upgrade the config from
+    /// older versions of `rx`
+    #[serde(default)]
+    pub version: u32,
+
+    /// Root path of the Cargo project, for the config of a project
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<PathBuf>,
+
     /// Represents the *last run* example
+    #[serde(default)]
     pub last_run: LastRun,
+
+    /// The *log level* settings last selected for each example, keyed by
+    /// the example name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub log_settings: BTreeMap<String, LogSettings>,
 }
 
 /// The details on the *last run* example.
-#[derive(Deserialize, Serialize, Default)]
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
 pub struct LastRun {
-    /// Example *name*
+    /// Example *name*; this is the first example, if multiple examples were run
     pub name: String,
 
+    /// Example *names*, if multiple examples were run
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<String>,
+
     /// Example *arguments* passed in via command line
     pub arguments: Vec<String>,
+
+    /// File piped in to the example's *stdin*, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stdin_file: Option<PathBuf>,
+
+    /// Text piped in to the example's *stdin*, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stdin_text: Option<String>,
+
+    /// Options which were passed in to `cargo`, such as `--release`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cargo_args: Vec<String>,
+
+    /// Working directory passed in via `--cwd`, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+
+    /// True if the examples were run in their own directory, via `--cwd-example`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cwd_example: bool,
+
+    /// Root path of the Cargo project the example is in; this is only
+    /// recorded in the *global* replay config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<PathBuf>,
+
+    /// Env variables which were set for each example, keyed by the
+    /// example name
+    ///
+    /// Note: this is the last field, as TOML tables need to come after
+    /// any values.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl LastRun {
+    /// Create a `LastRun` for the examples being run, along with the
+    /// *arguments* (and *stdin*) they are being called with.
+    pub fn new<'a>(names: &[String], args: &[&'a str], stdin: Option<&'a StdinSource>) -> Self {
+        let mut run = Self {
+            name: names.first().cloned().unwrap_or_default(),
+            arguments: args.iter().map(|&s| s.to_owned()).collect(),
+            ..Default::default()
+        };
+        if names.len() > 1 {
+            run.examples = names.to_vec();
+        }
+        run.set_stdin(stdin);
+        run
+    }
+
+    /// Return the names of the examples which were run.
+    pub fn examples(&self) -> Vec<String> {
+        if self.examples.is_empty() {
+            vec![self.name.clone()]
+        } else {
+            self.examples.clone()
+        }
+    }
+
+    /// Return the content which was piped in to the example's *stdin*, if any.
+    pub fn stdin(&self) -> Option<StdinSource> {
+        if let Some(ref path) = self.stdin_file {
+            Some(StdinSource::File(path.to_owned()))
+        } else {
+            self.stdin_text.clone().map(StdinSource::Text)
+        }
+    }
+
+    /// Return the run with any paths made relative to the project `root`
+    /// where possible, so that it can be shared with others.
+    pub fn strip_root(mut self, root: &Path) -> Self {
+        let strip = |path: PathBuf| match path.strip_prefix(root) {
+            Ok(p) if p.as_os_str().is_empty() => PathBuf::from("."),
+            Ok(p) => p.to_path_buf(),
+            Err(_) => path,
+        };
+
+        self.root = None;
+        self.cwd = self.cwd.map(strip);
+        self.stdin_file = self.stdin_file.map(strip);
+        self
+    }
+
+    /// Return the run with any relative paths resolved against the project
+    /// `root`; this is the inverse of [`strip_root`](LastRun::strip_root).
+    pub fn join_root(mut self, root: &Path) -> Self {
+        let join = |path: PathBuf| match path.to_str() {
+            Some(".") => root.to_path_buf(),
+            _ => root.join(path),
+        };
+
+        self.cwd = self.cwd.map(join);
+        self.stdin_file = self.stdin_file.map(join);
+        self
+    }
+
+    /// Record the content piped in to the example's *stdin*.
+    pub fn set_stdin(&mut self, stdin: Option<&StdinSource>) {
+        self.stdin_file = None;
+        self.stdin_text = None;
+
+        match stdin {
+            Some(StdinSource::File(path)) => self.stdin_file = Some(path.to_owned()),
+            Some(StdinSource::Text(text)) => self.stdin_text = Some(text.to_owned()),
+            None => {}
+        }
+    }
+}
+
+/// The *log level* settings selected for an example via `--log`.
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct LogSettings {
+    /// Value for the `RUST_LOG` env variable, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rust_log: Option<String>,
+
+    /// Value for the `RUST_BACKTRACE` env variable, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rust_backtrace: Option<String>,
+}
+
+/// An entry in the *run history*, which represents a single call to `rx`
+/// that ran one or more examples.
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct HistoryEntry {
+    /// The examples which were run, along with their *arguments* and
+    /// *cargo options*
+    #[serde(flatten)]
+    pub run: LastRun,
+
+    /// The *result* of running each example
+    #[serde(default)]
+    pub results: Vec<ExampleResult>,
+
+    /// When the run started, in seconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+/// The *result* of running an example.
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct ExampleResult {
+    /// Example *name*
+    pub name: String,
+
+    /// How long it took to build and run the example, in seconds
+    pub duration: f64,
+
+    /// Exit code of the example, or `None` if it was terminated by a signal
+    pub exit_code: Option<i32>,
+
+    /// True if the output of the example didn't match its snapshot, with
+    /// `--check-output`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub output_mismatch: bool,
+
+    /// True if the example was killed after it timed out, with `--timeout`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub timed_out: bool,
+}
+
+impl ExampleResult {
+    /// Return true if the example exited successfully, and its output
+    /// matched the snapshot (if checked).
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0) && !self.output_mismatch && !self.timed_out
+    }
 }
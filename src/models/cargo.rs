@@ -0,0 +1,99 @@
+use std::path::PathBuf;
+
+use serde::Deserialize;
+
+/// A *message* emitted by Cargo, when called with `--message-format json`.
+///
+/// Only the fields which are needed by `rx` are included.
+#[derive(Deserialize, Clone, Debug)]
+pub struct CargoMessage {
+    /// The *kind* of message, for ex. `compiler-artifact`
+    pub reason: String,
+
+    /// The target which the message is for, if any
+    #[serde(default)]
+    pub target: Option<CargoTarget>,
+
+    /// Path to the executable which was built, for a `compiler-artifact`
+    /// message of a binary or example target
+    #[serde(default)]
+    pub executable: Option<PathBuf>,
+
+    /// The diagnostic emitted by the compiler, for a `compiler-message`
+    /// message
+    #[serde(default)]
+    pub message: Option<CompilerMessage>,
+
+    /// Path to the `Cargo.toml` of the package which the message is for
+    #[serde(default)]
+    pub manifest_path: Option<PathBuf>,
+}
+
+/// A *diagnostic* emitted by the compiler, such as an error or warning.
+#[derive(Deserialize, Clone, Debug)]
+pub struct CompilerMessage {
+    /// The *level* of the diagnostic, for ex. `error` or `warning`
+    pub level: String,
+
+    /// The diagnostic as rendered by the compiler, if any
+    #[serde(default)]
+    pub rendered: Option<String>,
+
+    /// The main message of the diagnostic, for ex. `mismatched types`
+    #[serde(default)]
+    pub message: String,
+
+    /// Locations in the source code which the diagnostic points to
+    #[serde(default)]
+    pub spans: Vec<DiagnosticSpan>,
+}
+
+/// A *location* in the source code, which a diagnostic points to.
+#[derive(Deserialize, Clone, Debug)]
+pub struct DiagnosticSpan {
+    /// Path to the source file, which is relative to the workspace root
+    pub file_name: PathBuf,
+
+    /// The first line of the span, starting at 1
+    pub line_start: usize,
+
+    /// The first column of the span, starting at 1
+    pub column_start: usize,
+
+    /// True if this is the main location of the diagnostic
+    pub is_primary: bool,
+}
+
+/// A *target* (i.e. a library, binary, or example) in a Cargo package.
+#[derive(Deserialize, Clone, Debug)]
+pub struct CargoTarget {
+    /// Name of the target
+    pub name: String,
+
+    /// Kinds of the target, for ex. `["example"]` or `["bin"]`
+    pub kind: Vec<String>,
+}
+
+impl CargoMessage {
+    /// Return the path to the *executable* for a binary or example target,
+    /// if this is a `compiler-artifact` message for one.
+    pub fn executable(&self) -> Option<&PathBuf> {
+        let target = self.target.as_ref()?;
+
+        if self.reason == "compiler-artifact"
+            && target.kind.iter().any(|k| k == "bin" || k == "example")
+        {
+            self.executable.as_ref()
+        } else {
+            None
+        }
+    }
+}
+
+impl CompilerMessage {
+    /// Return the main location in the source code which the diagnostic
+    /// points to, if any.
+    pub fn primary_span(&self) -> Option<&DiagnosticSpan> {
+        self.spans.iter().find(|span| span.is_primary)
+    }
+}
@@ -0,0 +1,79 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use path_absolutize::*;
+use serde::Deserialize;
+
+use crate::{parse_duration, StdinSource};
+
+/// Per-example settings, which can be defined in a Cargo project under a
+/// `[package.metadata.rx.examples.<name>]` table in the `Cargo.toml` file.
+///
+/// For example:
+///
+/// ```toml
+/// [package.metadata.rx.examples.my_example]
+/// stdin = "examples/input.txt"
+/// cwd = "examples/my_example"
+/// args = ["--verbose", "input.txt"]
+/// redact = ["id: \\d+"]
+/// timeout = "30s"
+/// ```
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExampleMetadata {
+    /// File to pipe in to the example's *stdin*, relative to the project root
+    pub stdin: Option<PathBuf>,
+
+    /// Text to pipe in to the example's *stdin*
+    pub stdin_text: Option<String>,
+
+    /// Working directory to run the example in, relative to the project root
+    pub cwd: Option<PathBuf>,
+
+    /// Arguments to pass to the example when it's run via `--all`
+    #[serde(default)]
+    pub args: Vec<String>,
+
+    /// Regex patterns to redact from the output with `--check-output`
+    #[serde(default)]
+    pub redact: Vec<String>,
+
+    /// How long to wait on the example, before killing it, i.e. `30s`
+    pub timeout: Option<String>,
+}
+
+impl ExampleMetadata {
+    /// Return the default *stdin* for the example, if one is defined.
+    ///
+    /// # Arguments
+    /// * `root` - The base path to the Cargo project, which a *stdin* file
+    ///   path is relative to.
+    pub fn stdin_source(&self, root: &Path) -> io::Result<Option<StdinSource>> {
+        if let Some(ref path) = self.stdin {
+            let abs_path = path.absolutize_from(root)?;
+            Ok(Some(StdinSource::File(abs_path.into())))
+        } else {
+            Ok(self.stdin_text.clone().map(StdinSource::Text))
+        }
+    }
+
+    /// Return the default *timeout* for the example, if one is defined.
+    pub fn timeout(&self) -> Result<Option<Duration>, String> {
+        self.timeout.as_deref().map(parse_duration).transpose()
+    }
+
+    /// Return the default *working directory* for the example, if one is
+    /// defined.
+    ///
+    /// # Arguments
+    /// * `root` - The base path to the Cargo project, which the directory
+    ///   is relative to.
+    pub fn cwd(&self, root: &Path) -> io::Result<Option<PathBuf>> {
+        self.cwd
+            .as_ref()
+            .map(|path| Ok(path.absolutize_from(root)?.into()))
+            .transpose()
+    }
+}
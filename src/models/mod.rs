@@ -1,9 +1,17 @@
 mod args;
 mod cache;
+mod cargo;
+mod metadata;
+mod options;
 mod path;
 mod skim;
+mod units;
 
 pub use self::skim::*;
 pub use args::*;
 pub use cache::*;
+pub use cargo::*;
+pub use metadata::*;
+pub use options::*;
 pub use path::*;
+pub use units::*;
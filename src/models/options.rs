@@ -0,0 +1,66 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Additional options for running an example, which are not passed in to
+/// `cargo run` directly.
+#[derive(Clone, Debug, Default)]
+pub struct RunOptions {
+    /// *Environment variables* to set for the example process
+    pub env: BTreeMap<String, String>,
+
+    /// Content to pipe in to the *stdin* of the example process, if any
+    pub stdin: Option<StdinSource>,
+
+    /// The *directory* of the example, which is used to expand the
+    /// `{example_dir}` placeholder in arguments
+    pub example_dir: PathBuf,
+
+    /// The *working directory* to run the example process in, if it should
+    /// be different from the project root
+    pub cwd: Option<PathBuf>,
+
+    /// True to build the example with `cargo build`, and then run its
+    /// executable directly -- rather than with `cargo run`. This is always
+    /// the case if `cwd` is set.
+    pub exec: bool,
+
+    /// True to pipe the *stdout* and *stderr* of the example process,
+    /// rather than inheriting them -- so that its output can be captured
+    pub capture_output: bool,
+
+    /// True to show a progress spinner in place of the `cargo build` output,
+    /// which is only printed if the build fails
+    pub clean_output: bool,
+
+    /// Path to the *executable* of the example, if it was built up front
+    /// with `--prebuild`; it's then run directly, with no `cargo build`
+    pub executable: Option<PathBuf>,
+
+    /// How long to wait on the example process, before killing it
+    pub timeout: Option<Duration>,
+
+    /// Max size of the virtual memory of the example process, in bytes
+    pub max_memory: Option<u64>,
+
+    /// Max CPU time of the example process, in seconds
+    pub max_cpu_time: Option<u64>,
+
+    /// Command to run the executable of the example under, i.e. a debugger
+    /// such as `gdb --args`; this is empty if there's no wrapper
+    pub wrapper: Vec<String>,
+
+    /// True to run the example in the Miri interpreter, with `cargo miri
+    /// run` rather than `cargo run`
+    pub miri: bool,
+}
+
+/// Represents the *source* of content to pipe in to an example's *stdin*.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StdinSource {
+    /// Read *stdin* from a file, given as an absolute path
+    File(PathBuf),
+
+    /// Use the provided text as *stdin*
+    Text(String),
+}
@@ -216,6 +216,16 @@ impl ExampleFile {
             required_features,
         }
     }
+
+    /// Returns the *directory* of the example -- i.e. the sub-folder in the
+    /// case of a multi-file example or a crate, or else the folder which
+    /// contains the example file.
+    pub fn dir(&self) -> PathBuf {
+        match self.path_type {
+            ExampleType::Crate(ref cargo_toml, _) => cargo_toml.parent().unwrap().to_path_buf(),
+            _ => self.path.parent().unwrap().to_path_buf(),
+        }
+    }
 }
 
 impl Paths {
@@ -231,8 +241,14 @@ impl Paths {
     /// Raises an error if it cannot locate the base Cargo directory for the
     /// project.
     pub fn resolve() -> Result<Self> {
-        let current_dir = env::current_dir()?;
+        Self::resolve_from(env::current_dir()?)
+    }
 
+    /// Iterates backward from a directory `current_dir`, and locates the
+    /// base Cargo directory for the project.
+    ///
+    /// See [`Paths::resolve`] for more details.
+    pub fn resolve_from(current_dir: PathBuf) -> Result<Self> {
         let examples_folder = Path::new(EXAMPLES_FOLDER);
         let cargo_toml_file = Path::new(CARGO_TOML);
 
@@ -287,6 +303,25 @@ impl Paths {
         )))
     }
 
+    /// Returns the per-example settings for an example `name`, as defined
+    /// in a `[package.metadata.rx.examples.<name>]` table in the `Cargo.toml`
+    /// file.
+    pub fn example_metadata(&self, name: &str) -> Result<ExampleMetadata> {
+        let metadata = self
+            .manifest
+            .package
+            .as_ref()
+            .and_then(|p| p.metadata.as_ref())
+            .and_then(|m| m.get(BINARY_NAME))
+            .and_then(|m| m.get("examples"))
+            .and_then(|m| m.get(name));
+
+        match metadata {
+            Some(value) => Ok(value.clone().try_into()?),
+            None => Ok(ExampleMetadata::default()),
+        }
+    }
+
     /// Returns an ordered (A -> Z) mapping of file name to resolved file
     /// (`ExampleFile` objects) of each *example* file in the Cargo project.
     pub fn example_files(&self) -> Result<BTreeMap<Cow<'_, str>, ExampleFile>> {
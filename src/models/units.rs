@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+/// Parse a *duration* such as `30s`, `500ms`, `2m`, or `1h`; a number with
+/// no unit is in seconds.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (num, unit) = split_unit(s);
+
+    let secs_per_unit = match unit {
+        "ms" => 0.001,
+        "" | "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        _ => {
+            return Err(format!(
+                "invalid duration `{s}`; expected a unit of `ms`, `s`, `m`, or `h`"
+            ))
+        }
+    };
+
+    match num.parse::<f64>() {
+        Ok(num) if num > 0.0 => {
+            let secs = num * secs_per_unit;
+            // `Duration::from_secs_f64` panics if the value doesn't fit
+            if secs.is_finite() && secs < u64::MAX as f64 {
+                Ok(Duration::from_secs_f64(secs))
+            } else {
+                Err(format!("invalid duration `{s}`; the value is too large"))
+            }
+        }
+        _ => Err(format!(
+            "invalid duration `{s}`; expected i.e. `30s` or `2m`"
+        )),
+    }
+}
+
+/// Parse a *size* in bytes such as `512M`, `2G`, or `1024K`, where each
+/// unit is a power of 1024; a number with no unit is in bytes.
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let (num, unit) = split_unit(s);
+
+    let unit = unit.to_ascii_uppercase();
+    let unit = unit.trim_end_matches("IB").trim_end_matches('B');
+
+    let bytes_per_unit: u64 = match unit {
+        "" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        _ => {
+            return Err(format!(
+                "invalid size `{s}`; expected a unit of `K`, `M`, `G`, or `T`"
+            ))
+        }
+    };
+
+    match num
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(bytes_per_unit))
+    {
+        Some(bytes) if bytes > 0 => Ok(bytes),
+        _ => Err(format!("invalid size `{s}`; expected i.e. `512M` or `2G`")),
+    }
+}
+
+/// Split a string such as `30s` in to the number and the unit.
+fn split_unit(s: &str) -> (&str, &str) {
+    let idx = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    (&s[..idx], s[idx..].trim_start())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+        assert_eq!(parse_duration(" 45 "), Ok(Duration::from_secs(45)));
+        assert_eq!(parse_duration("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("0.25m"), Ok(Duration::from_secs(15)));
+    }
+
+    #[test]
+    fn test_parse_duration_invalid() {
+        for s in [
+            "", "0", "0s", "0.0m", "s", "1.2.3s", "-5s", "10d", "1e30s", "abc",
+        ] {
+            assert!(parse_duration(s).is_err(), "`{s}` should be invalid");
+        }
+    }
+
+    #[test]
+    fn test_parse_duration_overflow() {
+        assert!(parse_duration("99999999999999999999h").is_err());
+        assert!(parse_duration("99999999999999999999999999").is_err());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024"), Ok(1024));
+        assert_eq!(parse_size("1K"), Ok(1024));
+        assert_eq!(parse_size("512M"), Ok(512 << 20));
+        assert_eq!(parse_size("2G"), Ok(2 << 30));
+        assert_eq!(parse_size("2gb"), Ok(2 << 30));
+        assert_eq!(parse_size("1TiB"), Ok(1 << 40));
+        assert_eq!(parse_size(" 4 K "), Ok(4096));
+    }
+
+    #[test]
+    fn test_parse_size_invalid() {
+        for s in ["", "0", "0M", "M", "1.5G", "-1K", "10P", "abc"] {
+            assert!(parse_size(s).is_err(), "`{s}` should be invalid");
+        }
+    }
+
+    #[test]
+    fn test_parse_size_overflow() {
+        assert!(parse_size("99999999999999999999").is_err());
+        assert!(parse_size("16777216T").is_err());
+    }
+}
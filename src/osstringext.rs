@@ -33,6 +33,7 @@ mod ext_impl {
 pub trait OsStrExt2 {
     fn contains_byte(&self, byte: u8) -> bool;
     fn display_string(&self) -> String;
+    fn shell_escape(&self) -> String;
 }
 
 impl OsStrExt2 for OsStr {
@@ -97,4 +98,43 @@ impl OsStrExt2 for OsStr {
             self.to_str().unwrap().to_owned()
         }
     }
+
+    /// Return the *shell-escaped* string representation of an `OsStr`, so
+    /// that it can be copy-pasted in to a (POSIX) shell.
+    ///
+    /// If the argument contains any characters which have a special meaning
+    /// to the shell, it's wrapped in single quotes; any single quotes within
+    /// the argument are then escaped as `'\''`.
+    fn shell_escape(&self) -> String {
+        let value = self.to_string_lossy();
+
+        let is_safe = |c: char| c.is_ascii_alphanumeric() || "-_./:=,+@%^".contains(c);
+
+        if !value.is_empty() && value.chars().all(is_safe) {
+            value.into_owned()
+        } else {
+            format!("'{}'", value.replace('\'', r"'\''"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn escape(s: &str) -> String {
+        OsStr::new(s).shell_escape()
+    }
+
+    #[test]
+    fn test_shell_escape() {
+        assert_eq!(escape("--path=./data/in.txt"), "--path=./data/in.txt");
+        assert_eq!(escape("user@host:8080"), "user@host:8080");
+        assert_eq!(escape(""), "''");
+        assert_eq!(escape("hello world"), "'hello world'");
+        assert_eq!(escape("$HOME"), "'$HOME'");
+        assert_eq!(escape("a;b|c"), "'a;b|c'");
+        assert_eq!(escape("line 1\nline 2"), "'line 1\nline 2'");
+        assert_eq!(escape("it's"), r"'it'\''s'");
+    }
 }
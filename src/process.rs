@@ -0,0 +1,333 @@
+//! Platform-specific logic for the *example process*, such as applying
+//! resource limits, killing it once a timeout expires, and forwarding any
+//! signals which `rx` receives to it.
+//!
+use crate::*;
+pub(crate) use process_impl::*;
+
+use std::process::{Child, ExitStatus};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+/// How often to check if an example process with a timeout has exited
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Wait on an example process `child` to exit, or else kill it (along with
+/// its process group) once the `timeout` expires.
+///
+/// # Returns
+/// Returns the *exit status* of the example, and true if it timed out.
+///
+/// Once the example exits, `rx` takes back the terminal, and any processes
+/// which the example left behind in its process group are killed -- before
+/// the example is reaped, so that its process group can't be reused yet.
+pub(crate) fn wait_with_timeout(
+    child: &mut Child,
+    timeout: Option<Duration>,
+) -> Result<(ExitStatus, bool)> {
+    let result = wait_on(child, timeout);
+    detach_process(child);
+    let timed_out = result?;
+    Ok((child.wait()?, timed_out))
+}
+
+/// Wait on an example process `child` to exit, or else kill it once the
+/// `timeout` expires. The example is not reaped.
+///
+/// # Returns
+/// Returns true if the example timed out.
+fn wait_on(child: &mut Child, timeout: Option<Duration>) -> Result<bool> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => {
+            wait_for_exit(child, true)?;
+            return Ok(false);
+        }
+    };
+
+    let start = Instant::now();
+
+    loop {
+        if wait_for_exit(child, false)? {
+            return Ok(false);
+        }
+        if start.elapsed() >= timeout {
+            kill_process_group(child);
+            wait_for_exit(child, true)?;
+            return Ok(true);
+        }
+        sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(not(target_family = "windows"))]
+mod process_impl {
+    use super::*;
+
+    use std::io;
+    use std::os::unix::process::{CommandExt, ExitStatusExt};
+    use std::process::Command;
+    use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+    use std::sync::Once;
+
+    /// The *interrupt* signal, i.e. from Ctrl-C
+    pub(crate) const SIGINT: i32 = libc::SIGINT;
+
+    /// Signals which `rx` forwards to the example process group
+    const FORWARDED_SIGNALS: &[libc::c_int] = &[libc::SIGINT, libc::SIGTERM, libc::SIGHUP];
+
+    /// Process group of the example which is currently running, if any
+    static EXAMPLE_PGID: AtomicI32 = AtomicI32::new(0);
+
+    /// The last signal which was forwarded to the example, if any
+    static FORWARDED_SIGNAL: AtomicI32 = AtomicI32::new(0);
+
+    /// True if the terminal was handed over to the example process group
+    static HANDED_OVER_TERMINAL: AtomicBool = AtomicBool::new(false);
+
+    /// Signal handler which forwards a `signal` to the example process
+    /// group -- or if no example is running, exits as usual.
+    extern "C" fn forward_signal(signal: libc::c_int) {
+        // only async-signal-safe calls are allowed in a signal handler
+        let pgid = EXAMPLE_PGID.load(Ordering::SeqCst);
+        unsafe {
+            if pgid > 0 {
+                FORWARDED_SIGNAL.store(signal, Ordering::SeqCst);
+                libc::kill(-pgid, signal);
+            } else {
+                libc::signal(signal, libc::SIG_DFL);
+                libc::raise(signal);
+            }
+        }
+    }
+
+    /// Install the signal handlers which forward signals to the example
+    /// process group, if they're not installed already.
+    fn install_signal_handlers() {
+        static INSTALL: Once = Once::new();
+
+        INSTALL.call_once(|| unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction =
+                forward_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+
+            for &signal in FORWARDED_SIGNALS {
+                libc::sigaction(signal, &action, std::ptr::null_mut());
+            }
+        });
+    }
+
+    /// Configure the example process `cmd` with the options in `opts`.
+    ///
+    /// The example runs in its own process group, so that any signals can
+    /// be forwarded to it, and any processes it spawns can be killed along
+    /// with it. Any *resource limits* are applied to the example process
+    /// only, rather than to the `cargo build`.
+    pub(crate) fn configure_process(cmd: &mut Command, opts: &RunOptions) -> Result<()> {
+        let has_limits = opts.max_memory.is_some() || opts.max_cpu_time.is_some();
+
+        if has_limits && cfg!(not(target_os = "linux")) {
+            return Err("`--max-memory` and `--max-cpu-time` are only supported on Linux".into());
+        }
+
+        #[cfg(target_os = "linux")]
+        let (max_memory, max_cpu_time) = (opts.max_memory, opts.max_cpu_time);
+
+        // only async-signal-safe calls are allowed in between `fork` and `exec`
+        unsafe {
+            cmd.pre_exec(move || {
+                if libc::setpgid(0, 0) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                #[cfg(target_os = "linux")]
+                {
+                    if let Some(bytes) = max_memory {
+                        if libc::setrlimit(libc::RLIMIT_AS, &rlimit(bytes, bytes)) != 0 {
+                            return Err(io::Error::last_os_error());
+                        }
+                    }
+                    // the example gets a `SIGXCPU` at the soft limit, and
+                    // is killed at the hard limit
+                    if let Some(secs) = max_cpu_time {
+                        if libc::setrlimit(libc::RLIMIT_CPU, &rlimit(secs, secs + 1)) != 0 {
+                            return Err(io::Error::last_os_error());
+                        }
+                    }
+                }
+                Ok(())
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Return a resource limit with the `soft` and `hard` limits.
+    #[cfg(target_os = "linux")]
+    fn rlimit(soft: u64, hard: u64) -> libc::rlimit {
+        libc::rlimit {
+            rlim_cur: soft as libc::rlim_t,
+            rlim_max: hard as libc::rlim_t,
+        }
+    }
+
+    /// Kill the example process `child`, along with its process group if
+    /// it runs in its own group.
+    pub(crate) fn kill_process_group(child: &mut Child) {
+        let pid = child.id() as libc::pid_t;
+        // only kill the group if the child is its leader, i.e. not `rx`'s group
+        if unsafe { libc::getpgid(pid) } == pid {
+            unsafe { libc::kill(-pid, libc::SIGKILL) };
+        } else {
+            let _ = child.kill();
+        }
+    }
+
+    /// Attach to an example process `child` which was just spawned: any
+    /// signals which `rx` receives are forwarded to its process group, and
+    /// the terminal is handed over to it -- so that Ctrl-C goes to the
+    /// example, and it can read from the terminal.
+    pub(crate) fn attach_process(child: &Child) {
+        install_signal_handlers();
+
+        let pid = child.id() as libc::pid_t;
+        FORWARDED_SIGNAL.store(0, Ordering::SeqCst);
+
+        unsafe {
+            // the child sets its group too, but it may not have run yet
+            libc::setpgid(pid, pid);
+            if libc::getpgid(pid) != pid {
+                return;
+            }
+            EXAMPLE_PGID.store(pid, Ordering::SeqCst);
+
+            // only hand over the terminal if `rx` is in the foreground
+            let tty = libc::STDIN_FILENO;
+            if libc::isatty(tty) == 1
+                && libc::tcgetpgrp(tty) == libc::getpgrp()
+                && libc::tcsetpgrp(tty, pid) == 0
+            {
+                HANDED_OVER_TERMINAL.store(true, Ordering::SeqCst);
+                // the example is stopped if it read from the terminal before
+                // it was handed over, so resume it
+                libc::kill(-pid, libc::SIGCONT);
+            }
+        }
+    }
+
+    /// Wait for the example process `child` to exit, without reaping it --
+    /// so that its pid, and so its process group, can't be reused until
+    /// `child` is waited on. If `block` is false, this returns immediately.
+    ///
+    /// # Returns
+    /// Returns true if the example has exited.
+    pub(crate) fn wait_for_exit(child: &mut Child, block: bool) -> io::Result<bool> {
+        let pid = child.id() as libc::id_t;
+        let mut options = libc::WEXITED | libc::WNOWAIT;
+        if !block {
+            options |= libc::WNOHANG;
+        }
+
+        loop {
+            let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+            if unsafe { libc::waitid(libc::P_PID, pid, &mut info, options) } == 0 {
+                // with `WNOHANG`, the pid is left as zero if it hasn't exited
+                return Ok(unsafe { info.si_pid() } != 0);
+            }
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        }
+    }
+
+    /// Detach from an example process `child` which has exited, but not yet
+    /// been reaped: `rx` takes back the terminal, and any processes left
+    /// behind in the example's process group are killed.
+    pub(crate) fn detach_process(child: &Child) {
+        let pid = child.id() as libc::pid_t;
+        if EXAMPLE_PGID
+            .compare_exchange(pid, 0, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return;
+        }
+
+        unsafe {
+            if HANDED_OVER_TERMINAL.swap(false, Ordering::SeqCst) {
+                // `rx` is in the background, so it'd be stopped by `SIGTTOU`
+                // when taking back the terminal
+                let handler = libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+                libc::tcsetpgrp(libc::STDIN_FILENO, libc::getpgrp());
+                libc::signal(libc::SIGTTOU, handler);
+            }
+            // i.e. the workers of a server, which would otherwise be orphaned
+            libc::kill(-pid, libc::SIGKILL);
+        }
+    }
+
+    /// Return the signal which *interrupted* an example that exited with
+    /// `status` -- i.e. `SIGINT` from Ctrl-C, or a signal which `rx`
+    /// received and forwarded to it -- if any.
+    pub(crate) fn interrupt_signal(status: &ExitStatus) -> Option<i32> {
+        match FORWARDED_SIGNAL.swap(0, Ordering::SeqCst) {
+            0 => status.signal().filter(|&signal| signal == libc::SIGINT),
+            signal => Some(signal),
+        }
+    }
+}
+
+#[cfg(target_family = "windows")]
+mod process_impl {
+    use super::*;
+
+    use std::io;
+    use std::process::Command;
+
+    /// The *interrupt* signal, i.e. from Ctrl-C
+    pub(crate) const SIGINT: i32 = 2;
+
+    /// Configure the example process `cmd` with the options in `opts`.
+    ///
+    /// Resource limits are not supported on Windows.
+    pub(crate) fn configure_process(_cmd: &mut Command, opts: &RunOptions) -> Result<()> {
+        if opts.max_memory.is_some() || opts.max_cpu_time.is_some() {
+            return Err("`--max-memory` and `--max-cpu-time` are only supported on Linux".into());
+        }
+        Ok(())
+    }
+
+    /// Kill the example process `child`.
+    pub(crate) fn kill_process_group(child: &mut Child) {
+        let _ = child.kill();
+    }
+
+    /// Attach to an example process `child` which was just spawned.
+    ///
+    /// Signals are not forwarded on Windows, as Ctrl-C already goes to all
+    /// processes attached to the console.
+    pub(crate) fn attach_process(_child: &Child) {}
+
+    /// Wait for the example process `child` to exit. If `block` is false,
+    /// this returns immediately.
+    ///
+    /// # Returns
+    /// Returns true if the example has exited.
+    pub(crate) fn wait_for_exit(child: &mut Child, block: bool) -> io::Result<bool> {
+        if block {
+            child.wait().map(|_| true)
+        } else {
+            child.try_wait().map(|status| status.is_some())
+        }
+    }
+
+    /// Detach from an example process `child` which has exited.
+    pub(crate) fn detach_process(_child: &Child) {}
+
+    /// Return the signal which *interrupted* an example that exited with
+    /// `status`, if any.
+    pub(crate) fn interrupt_signal(_status: &ExitStatus) -> Option<i32> {
+        None
+    }
+}
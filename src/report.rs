@@ -0,0 +1,273 @@
+//! Logic for writing *reports* on a run via `--report`, such as a JUnit XML
+//! or JSON report, so CI systems can show the health of each example.
+//!
+use crate::*;
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::json;
+
+/// Max number of lines kept from the end of an example's output
+const TAIL_LINES: usize = 50;
+
+/// Max number of bytes kept from the end of an example's output
+pub(crate) const TAIL_BYTES: usize = 16 * 1024;
+
+/// Reason an example was skipped, when an earlier example failed
+pub(crate) const SKIPPED_FAILED: &str = "skipped after an earlier example failed";
+
+/// Reason an example was skipped, when the run was interrupted
+pub(crate) const SKIPPED_INTERRUPTED: &str = "skipped after the run was interrupted";
+
+/// The *outcome* of an example in a run.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Outcome {
+    /// The example ran, and exited successfully
+    Passed,
+    /// The example failed to build, or exited with an error
+    Failed,
+    /// The example was not run, as an earlier example failed, or the run
+    /// was interrupted
+    Skipped,
+}
+
+/// The details on an example in a run, for a report.
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct ReportEntry {
+    /// Example *name*
+    pub name: String,
+
+    /// Command which ran the example, as displayed -- or which built it, if
+    /// the build failed
+    pub command: String,
+
+    /// Outcome of the example
+    pub outcome: Outcome,
+
+    /// Exit code of the example, or `None` if it was terminated by a
+    /// signal, or was not run
+    pub exit_code: Option<i32>,
+
+    /// Exit status of the example, as displayed, i.e. `exit status: 1` --
+    /// or the reason it was skipped
+    pub status: Option<String>,
+
+    /// How long it took to build and run the example, in seconds
+    pub duration: f64,
+
+    /// The last lines the example wrote to *stdout*
+    pub stdout: String,
+
+    /// The last lines the example (or `cargo`) wrote to *stderr*
+    pub stderr: String,
+}
+
+impl ReportEntry {
+    /// Create a `ReportEntry` for an example `name` which was skipped, for
+    /// the given `reason`.
+    pub fn skipped(name: &str, reason: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            command: String::new(),
+            outcome: Outcome::Skipped,
+            exit_code: None,
+            status: Some(reason.to_owned()),
+            duration: 0.0,
+            stdout: String::new(),
+            stderr: String::new(),
+        }
+    }
+}
+
+/// Return the last `TAIL_LINES` lines of `output`, which is the *tail* of
+/// the output recorded in a report.
+pub(crate) fn output_tail(output: &[u8]) -> String {
+    let start = output.len().saturating_sub(TAIL_BYTES);
+    let output = String::from_utf8_lossy(&output[start..]);
+
+    let lines: Vec<&str> = output.lines().collect();
+    lines[lines.len().saturating_sub(TAIL_LINES)..].join("\n")
+}
+
+/// Return the name of the Cargo project, for a report.
+fn project_name(dir: &Paths) -> String {
+    match dir.manifest.package {
+        Some(ref package) => package.name.clone(),
+        None => dir
+            .root_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    }
+}
+
+/// Write each report in `reports` for a run, with the details on each
+/// example in `entries`.
+///
+/// # Arguments
+///
+/// * `reports` - the reports to write, as passed in via `--report`.
+/// * `dir` - path info on the Cargo project.
+/// * `entries` - the details on each example in the run.
+/// * `timestamp` - when the run started, in seconds since the Unix epoch.
+pub(crate) fn write_reports(
+    reports: &[ReportSpec],
+    dir: &Paths,
+    entries: &[ReportEntry],
+    timestamp: u64,
+) -> Result<()> {
+    let project = project_name(dir);
+
+    for report in reports {
+        let data = match report.format {
+            ReportFormat::Junit => junit_report(&project, entries),
+            ReportFormat::Json => json_report(&project, entries, timestamp)?,
+        };
+
+        write_file(&report.path, &data)
+            .map_err(|e| format!("failed to write `{}`: {e}", report.path.display()))?;
+
+        println!("{} {}", "Report:".cyan().bold(), report.path.display());
+    }
+
+    Ok(())
+}
+
+/// Write `data` to the file at `path`, creating any parent folders.
+fn write_file(path: &Path, data: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, data)
+}
+
+/// Return the number of `entries` with the given `outcome`.
+fn count(entries: &[ReportEntry], outcome: Outcome) -> usize {
+    entries.iter().filter(|e| e.outcome == outcome).count()
+}
+
+/// Return a JSON report on a run.
+fn json_report(project: &str, entries: &[ReportEntry], timestamp: u64) -> Result<String> {
+    let report = json!({
+        "project": project,
+        "timestamp": timestamp,
+        "duration": entries.iter().map(|e| e.duration).sum::<f64>(),
+        "passed": count(entries, Outcome::Passed),
+        "failed": count(entries, Outcome::Failed),
+        "skipped": count(entries, Outcome::Skipped),
+        "examples": entries,
+    });
+
+    Ok(serde_json::to_string_pretty(&report)?)
+}
+
+/// Return a JUnit XML report on a run, where each example is a test case.
+fn junit_report(project: &str, entries: &[ReportEntry]) -> String {
+    let project = xml_escape(project);
+    let duration: f64 = entries.iter().map(|e| e.duration).sum();
+    let counts = format!(
+        r#"tests="{}" failures="{}" skipped="{}" time="{duration:.3}""#,
+        entries.len(),
+        count(entries, Outcome::Failed),
+        count(entries, Outcome::Skipped),
+    );
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(xml, r#"<testsuites name="{BINARY_NAME}" {counts}>"#);
+    let _ = writeln!(xml, r#"  <testsuite name="{project}" {counts}>"#);
+
+    for entry in entries {
+        let _ = writeln!(
+            xml,
+            r#"    <testcase name="{}" classname="{project}.examples" time="{:.3}">"#,
+            xml_escape(&entry.name),
+            entry.duration
+        );
+
+        match entry.outcome {
+            Outcome::Passed => {}
+            Outcome::Failed => {
+                let _ = writeln!(
+                    xml,
+                    r#"      <failure message="{}" type="failure">{}</failure>"#,
+                    xml_escape(entry.status.as_deref().unwrap_or("failed")),
+                    xml_escape(&entry.command)
+                );
+            }
+            Outcome::Skipped => {
+                let _ = writeln!(
+                    xml,
+                    r#"      <skipped message="{}"/>"#,
+                    xml_escape(entry.status.as_deref().unwrap_or("skipped"))
+                );
+            }
+        }
+
+        if !entry.stdout.is_empty() {
+            let _ = writeln!(
+                xml,
+                "      <system-out>{}</system-out>",
+                xml_escape(&entry.stdout)
+            );
+        }
+        if !entry.stderr.is_empty() {
+            let _ = writeln!(
+                xml,
+                "      <system-err>{}</system-err>",
+                xml_escape(&entry.stderr)
+            );
+        }
+
+        xml.push_str("    </testcase>\n");
+    }
+
+    xml.push_str("  </testsuite>\n");
+    xml.push_str("</testsuites>\n");
+
+    xml
+}
+
+/// Escape the `text` for use in XML, and drop any characters which are
+/// not allowed in XML -- such as the escape codes for terminal colors.
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xml_escape() {
+        assert_eq!(
+            xml_escape(r#"<a href="x">Tom & 'Jerry'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; &apos;Jerry&apos;&lt;/a&gt;"
+        );
+        assert_eq!(xml_escape("line 1\n\tline 2\r\n"), "line 1\n\tline 2\r\n");
+        // the `ESC` of terminal color codes is not allowed in XML, so is dropped
+        assert_eq!(xml_escape("\u{1b}[31merror\u{1b}[0m: ✗"), "[31merror[0m: ✗");
+        assert_eq!(xml_escape("nul\0bell\u{7}"), "nulbell");
+    }
+}
@@ -1,10 +1,15 @@
 use crate::*;
 
-use std::ffi::OsStr;
-use std::path::Path;
-use std::process::Command;
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
 
 use cargo_options::CommonOptions;
+use cargo_toml::Manifest;
 use colored::Colorize;
 
 /// Defines the logic for running `cargo run --example`
@@ -15,11 +20,19 @@ pub trait RunExampleExt {
     ///
     /// * `ex_type` - Type of example.
     /// * `root_path` - the base path to the Cargo directory with a
-    ///                 `Cargo.toml` file.
+    ///   `Cargo.toml` file.
     /// * `name` - the name of the Cargo example to run.
-    /// * `args` - arguments to pass to the example script.
+    /// * `args` - arguments to pass to the example script; any placeholders
+    ///   such as `{root}`, `{example_dir}`, `{target_dir}`, and
+    ///   `${ENV}` are expanded.
     /// * `required_features` - any required features to enable when running
-    ///                         the example.
+    ///   the example.
+    /// * `opts` - additional options for running the example, such as any
+    ///   env variables to set, or content to pipe in to *stdin*.
+    ///
+    /// # Returns
+    /// Returns the *exit status* of the example, or of `cargo` in case the
+    /// example fails to build.
     fn run_example<'a, T: IntoIterator>(
         &self,
         ex_type: &'a ExampleType,
@@ -27,51 +40,738 @@ pub trait RunExampleExt {
         name: &'a str,
         args: T,
         required_features: &'a Option<String>,
-    ) -> Result<()>
+        opts: &'a RunOptions,
+    ) -> Result<ExitStatus>
+    where
+        <T as IntoIterator>::Item: AsRef<OsStr>;
+}
+
+/// Defines the logic for building an example, without running it
+pub(crate) trait BuildExampleExt {
+    /// Call `cargo build` on an example `name`, without running it.
+    ///
+    /// See [`run_example`](RunExampleExt::run_example) for a description
+    /// of the arguments.
+    ///
+    /// # Returns
+    /// Returns the *exit status* of `cargo`.
+    fn build_example<'a>(
+        &self,
+        ex_type: &'a ExampleType,
+        root_path: &'a Path,
+        name: &'a str,
+        required_features: &'a Option<String>,
+    ) -> Result<ExitStatus>;
+}
+
+/// Defines the logic for exporting the shell command for an example
+pub(crate) trait ExportExampleExt {
+    /// Return the *shell command* which runs an example `name` with
+    /// `cargo run --example`, so it can be copy-pasted in to a shell.
+    ///
+    /// The command is run from the project root, and changes to the working
+    /// directory of the example (in a subshell) if needed; it also sets any
+    /// env variables and *stdin* in `opts`. Any paths in the command are
+    /// relative, so that it works wherever the project is checked out.
+    ///
+    /// See [`run_example`](RunExampleExt::run_example) for a description
+    /// of the arguments.
+    fn export_example<'a, T: IntoIterator>(
+        &self,
+        ex_type: &'a ExampleType,
+        root_path: &'a Path,
+        name: &'a str,
+        args: T,
+        required_features: &'a Option<String>,
+        opts: &'a RunOptions,
+    ) -> Result<String>
     where
         <T as IntoIterator>::Item: AsRef<OsStr>;
 }
 
+/// Defines the logic for exporting the bare `cargo` command for an example
+pub(crate) trait ExportCargoCommandExt {
+    /// Return the `cargo run --example` command for an example `name`, to
+    /// run from the project root -- without changing directories, or any
+    /// env variables and *stdin*.
+    ///
+    /// See [`run_example`](RunExampleExt::run_example) for a description
+    /// of the arguments.
+    fn export_cargo_command<'a, T: IntoIterator>(
+        &self,
+        ex_type: &'a ExampleType,
+        root_path: &'a Path,
+        name: &'a str,
+        args: T,
+        required_features: &'a Option<String>,
+        opts: &'a RunOptions,
+    ) -> Result<String>
+    where
+        <T as IntoIterator>::Item: AsRef<OsStr>;
+}
+
+/// Defines the logic for printing the commands to run an example, rather
+/// than running it, via `--dry-run`
+pub(crate) trait DryRunExampleExt {
+    /// Return the *shell commands* which `rx` runs for an example `name`,
+    /// from the project root -- i.e. `cargo build` followed by the example's
+    /// executable, when it's run directly rather than with `cargo run`. Any
+    /// timeout and limits are shown in a comment.
+    ///
+    /// See [`run_example`](RunExampleExt::run_example) for a description
+    /// of the arguments.
+    fn dry_run_example<'a, T: IntoIterator>(
+        &self,
+        ex_type: &'a ExampleType,
+        root_path: &'a Path,
+        name: &'a str,
+        args: T,
+        required_features: &'a Option<String>,
+        opts: &'a RunOptions,
+    ) -> Result<Vec<String>>
+    where
+        <T as IntoIterator>::Item: AsRef<OsStr>;
+}
+
+/// An example process which was *spawned*, via
+/// [`spawn_example`](SpawnExampleExt::spawn_example).
+pub(crate) enum Spawned {
+    /// The example is running as a child process, along with the command
+    /// it was run with -- as displayed
+    Running(Child, String),
+
+    /// The example exited without running, i.e. if the build failed -- along
+    /// with the output of the build
+    Exited(BuildOutput),
+}
+
+/// Defines the logic for spawning an example, without waiting on it to exit
+pub(crate) trait SpawnExampleExt {
+    /// Spawn the example `name` as a child process, and return it -- or
+    /// the exit status of `cargo`, in case the example fails to build.
+    ///
+    /// See [`run_example`](RunExampleExt::run_example) for a description
+    /// of the arguments.
+    fn spawn_example<'a, T: IntoIterator>(
+        &self,
+        ex_type: &'a ExampleType,
+        root_path: &'a Path,
+        name: &'a str,
+        args: T,
+        required_features: &'a Option<String>,
+        opts: &'a RunOptions,
+    ) -> Result<Spawned>
+    where
+        <T as IntoIterator>::Item: AsRef<OsStr>;
+}
+
+/// Defines the logic for building several examples up front, so they can
+/// be run directly afterwards
+pub(crate) trait PrebuildExamplesExt {
+    /// Build all `examples` with `cargo build`, in a single call for the
+    /// examples in the project -- and in one call for each nested crate.
+    ///
+    /// # Arguments
+    ///
+    /// * `root_path` - the base path to the Cargo directory with a
+    ///   `Cargo.toml` file.
+    /// * `examples` - the name and file of each example to build.
+    /// * `clean_output` - true to show a progress spinner in place of the
+    ///   build output.
+    ///
+    /// # Returns
+    /// Returns the *exit status* of `cargo` (i.e. of the first build which
+    /// failed), along with the *executable* for each example name which
+    /// was built.
+    fn prebuild_examples(
+        &self,
+        root_path: &Path,
+        examples: &[(&str, &ExampleFile)],
+        clean_output: bool,
+    ) -> Result<(ExitStatus, BTreeMap<String, PathBuf>)>;
+}
+
 /// Add `run --example <name>` (or `run --manifest-path <file>) as arguments to Command `cmd`
+///
+/// Note that `subcommand` is usually `run`, but can also be `build` for instance.
 #[inline]
-fn add_run_arg(cmd: &mut Command, name: &str, ex_type: &ExampleType, root_path: &Path) {
+fn add_run_arg(
+    cmd: &mut Command,
+    subcommand: &str,
+    name: &str,
+    ex_type: &ExampleType,
+    root_path: &Path,
+) {
     /// call `run` with a `--manifest-path`
     #[inline]
     fn run_with_manifest<'a>(
         cmd: &'a mut Command,
+        subcommand: &'a str,
         root_path: &'a Path,
         manifest_path: &'a Path,
     ) -> &'a mut Command {
-        cmd.arg("run")
+        cmd.arg(subcommand)
             .arg("--manifest-path")
             .arg(manifest_path.strip_prefix(root_path).unwrap())
     }
     match ex_type {
         // call `run` with `--manifest-path`
-        ExampleType::Crate(manifest_path, None) => run_with_manifest(cmd, root_path, manifest_path),
+        ExampleType::Crate(manifest_path, None) => {
+            run_with_manifest(cmd, subcommand, root_path, manifest_path)
+        }
         // call `run` with `--manifest-path` and `--bin`
         ExampleType::Crate(manifest_path, Some(bin)) => {
-            run_with_manifest(cmd, root_path, manifest_path)
+            run_with_manifest(cmd, subcommand, root_path, manifest_path)
                 .arg("--bin")
                 .arg(bin)
         }
         // call `run --example`
-        _ => cmd.arg("run").arg("--example").arg(name),
+        _ => cmd.arg(subcommand).arg("--example").arg(name),
     };
 }
 
-impl RunExampleExt for CommonOptions {
-    fn run_example<'a, T: IntoIterator>(
+/// Return the *target directory* where Cargo places build artifacts for an
+/// example, which is used to expand the `{target_dir}` placeholder in arguments.
+fn target_dir(opts: &CommonOptions, ex_type: &ExampleType, root_path: &Path) -> PathBuf {
+    if let Some(ref target_dir) = opts.target_dir {
+        return root_path.join(target_dir);
+    }
+    if let Some(target_dir) = env::var_os("CARGO_TARGET_DIR") {
+        return root_path.join(target_dir);
+    }
+    match ex_type {
+        // a nested crate has its own `target/` folder
+        ExampleType::Crate(manifest_path, _) => manifest_path.parent().unwrap().join("target"),
+        _ => root_path.join("target"),
+    }
+}
+
+/// Return the `path` relative to the directory `base`, i.e. `../Cargo.toml`,
+/// if both are absolute paths in the same folder tree. Otherwise, the
+/// `path` is returned as-is.
+fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let mut path_parts = path.components().peekable();
+    let mut base_parts = base.components().peekable();
+
+    let mut common = 0;
+    while let (Some(a), Some(b)) = (path_parts.peek(), base_parts.peek()) {
+        if a != b {
+            break;
+        }
+        path_parts.next();
+        base_parts.next();
+        common += 1;
+    }
+
+    // only the root (i.e. `/`) is in common
+    if !path.is_absolute() || !base.is_absolute() || common <= 1 {
+        return path.to_path_buf();
+    }
+
+    let relative: PathBuf = base_parts
+        .map(|_| Component::ParentDir)
+        .chain(path_parts)
+        .collect();
+
+    match relative.as_os_str().is_empty() {
+        true => PathBuf::from("."),
+        false => relative,
+    }
+}
+
+/// Return the path to the `Cargo.toml` of the package which contains an
+/// example.
+fn manifest_path(opts: &CommonOptions, ex_type: &ExampleType, root_path: &Path) -> PathBuf {
+    match ex_type {
+        ExampleType::Crate(manifest_path, _) => manifest_path.to_path_buf(),
+        _ => match opts.manifest_path {
+            Some(ref manifest_path) => root_path.join(manifest_path),
+            None => root_path.join(CARGO_TOML),
+        },
+    }
+}
+
+/// Set the env variables which `cargo run` sets, on the command `cmd` which
+/// runs the `executable` of an example directly -- i.e. the `CARGO_PKG_*`
+/// variables for the package at `manifest_path`, and the search path for
+/// any *dynamic libraries* in the target directory.
+fn add_cargo_env(cmd: &mut Command, manifest_path: &Path, executable: &Path) -> Result<()> {
+    cmd.env("CARGO_MANIFEST_DIR", manifest_path.parent().unwrap());
+
+    let manifest = Manifest::from_slice(&std::fs::read(manifest_path)?)
+        .map_err(|e| format!("failed to parse `{}`: {e}", manifest_path.display()))?;
+
+    if let Some(pkg) = manifest.package {
+        let (version, _build) = pkg.version.split_once('+').unwrap_or((&pkg.version, ""));
+        let (version, pre) = version.split_once('-').unwrap_or((version, ""));
+        let mut parts = version.splitn(3, '.');
+
+        cmd.env("CARGO_PKG_NAME", &pkg.name)
+            .env("CARGO_PKG_VERSION", &pkg.version)
+            .env("CARGO_PKG_VERSION_MAJOR", parts.next().unwrap_or_default())
+            .env("CARGO_PKG_VERSION_MINOR", parts.next().unwrap_or_default())
+            .env("CARGO_PKG_VERSION_PATCH", parts.next().unwrap_or_default())
+            .env("CARGO_PKG_VERSION_PRE", pre)
+            .env("CARGO_PKG_AUTHORS", pkg.authors.join(":"))
+            .env("CARGO_PKG_DESCRIPTION", pkg.description.unwrap_or_default())
+            .env("CARGO_PKG_HOMEPAGE", pkg.homepage.unwrap_or_default())
+            .env("CARGO_PKG_REPOSITORY", pkg.repository.unwrap_or_default())
+            .env("CARGO_PKG_LICENSE", pkg.license.unwrap_or_default())
+            .env(
+                "CARGO_PKG_LICENSE_FILE",
+                pkg.license_file.unwrap_or_default(),
+            )
+            .env(
+                "CARGO_PKG_RUST_VERSION",
+                pkg.rust_version.unwrap_or_default(),
+            );
+    }
+
+    // the executable is in `target/<profile>/`, or in its `examples/` folder,
+    // and any dynamic libraries it links against are in `target/<profile>/deps/`
+    let mut profile_dir = executable.parent().unwrap();
+    if profile_dir.ends_with(EXAMPLES_FOLDER) {
+        profile_dir = profile_dir.parent().unwrap();
+    }
+
+    let mut search_path = vec![profile_dir.join("deps"), profile_dir.to_path_buf()];
+    if let Some(path) = env::var_os(DYLIB_PATH) {
+        search_path.extend(env::split_paths(&path));
+    }
+    cmd.env(DYLIB_PATH, env::join_paths(search_path)?);
+
+    Ok(())
+}
+
+/// Add the arguments `args` to pass to an example to the command `cmd`,
+/// expanding any `placeholders` such as `{root}`.
+///
+/// If `strip_separator` is true, a leading `--` separator is skipped, as
+/// it's only needed for `cargo run`.
+fn add_example_args<T: IntoIterator>(
+    cmd: &mut Command,
+    args: T,
+    placeholders: &[(&str, &Path)],
+    strip_separator: bool,
+) -> Result<()>
+where
+    <T as IntoIterator>::Item: AsRef<OsStr>,
+{
+    let mut args = args.into_iter().peekable();
+
+    if strip_separator {
+        args.next_if(|arg| arg.as_ref() == OsStr::new("--"));
+    }
+
+    for arg in args {
+        let arg = arg.as_ref();
+        match arg.to_str() {
+            Some(arg) => cmd.arg(expand_placeholders(arg, placeholders)?),
+            // leave any arguments which are not valid UTF-8 as-is
+            None => cmd.arg(arg),
+        };
+    }
+
+    Ok(())
+}
+
+/// Return the *command line* for the command `cmd`, with each argument
+/// shell-escaped.
+pub(crate) fn command_line(cmd: &Command) -> String {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(OsStr::shell_escape)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Print the command `cmd` which is being run, along with an optional
+/// `prefix` (such as env variables) and `suffix` (such as *stdin*).
+fn print_command(cmd: &Command, prefix: &str, suffix: &str) {
+    let cmd_line = command_line(cmd);
+
+    #[cfg(target_family = "windows")]
+    println!(
+        " {} {}{}{}",
+        ">>".white().bold(),
+        prefix.bright_blue().italic(),
+        cmd_line.as_str().bright_blue().italic(),
+        suffix.bright_blue().italic()
+    );
+
+    #[cfg(not(target_family = "windows"))]
+    println!(
+        " {} {}{}{}",
+        "❯❯".white().bold(),
+        prefix.blue().italic(),
+        cmd_line.as_str().blue().italic(),
+        suffix.blue().italic()
+    );
+}
+
+/// Run the `cargo build` command `build` for an example `name`, and return
+/// its output along with the path to the *executable* of the example, if
+/// the build succeeds.
+///
+/// The executable is matched on the target name and kind, so that any
+/// other binaries which are built along with it are never run instead; it's
+/// an error if the build succeeds without one.
+///
+/// Note that `build` should be called with the [`build_message_format`],
+/// so we can parse the path to the executable from the output.
+fn build_executable(
+    build: &mut Command,
+    ex_type: &ExampleType,
+    root_path: &Path,
+    name: &str,
+    clean_output: bool,
+) -> Result<(BuildOutput, Option<PathBuf>)> {
+    let mut output = build_executables(build, root_path, clean_output)?;
+    if !output.status.success() {
+        return Ok((output, None));
+    }
+
+    // a nested crate with no `--bin` is run with its only binary target
+    let (kind, target) = match ex_type {
+        ExampleType::Crate(_, Some(bin)) => ("bin", Some(bin.as_str())),
+        ExampleType::Crate(_, None) => ("bin", None),
+        _ => ("example", Some(name)),
+    };
+
+    let mut matching = std::mem::take(&mut output.executables)
+        .into_iter()
+        .filter(|(t, _)| {
+            t.kind.iter().any(|k| k == kind) && target.iter().all(|&target| t.name == target)
+        });
+
+    match (matching.next(), matching.next()) {
+        (Some((_, exe)), None) => Ok((output, Some(exe))),
+        (Some(_), Some(_)) => Err(format!(
+            "found several executables for example `{name}`; \
+            set `default-run`, or only keep one binary target in its `Cargo.toml`"
+        )
+        .into()),
+        (None, _) => Err(format!("could not locate the executable for example `{name}`").into()),
+    }
+}
+
+/// Run the `cargo build` command `build` for the project at `root_path`,
+/// and return its output -- including the target and path of each
+/// *executable* which was built, and any compiler diagnostics.
+///
+/// The diagnostics are printed as they're emitted, unless `clean_output`
+/// is true, in which case a progress spinner is shown in place of the
+/// build output.
+///
+/// See [`build_executable`].
+fn build_executables(
+    build: &mut Command,
+    root_path: &Path,
+    clean_output: bool,
+) -> Result<BuildOutput> {
+    if clean_output {
+        return build_quietly(build, root_path);
+    }
+
+    print_command(build, "", "");
+
+    let mut child = build.stdout(Stdio::piped()).spawn()?;
+    let stdout = child.stdout.take().expect("Failed to open stdout");
+
+    let mut executables = Vec::new();
+    let mut diagnostics = Vec::new();
+
+    for line in BufReader::new(stdout).lines() {
+        if let Ok(msg) = serde_json::from_str::<CargoMessage>(&line?) {
+            if let (Some(exe), Some(target)) = (msg.executable(), msg.target.as_ref()) {
+                executables.push((target.clone(), exe.to_owned()));
+            }
+            if let Some(rendered) = msg.message.as_ref().and_then(|m| m.rendered.as_ref()) {
+                eprint!("{rendered}");
+            }
+            diagnostics.extend(Diagnostic::from_message(msg, root_path));
+        }
+    }
+
+    Ok(BuildOutput {
+        command: command_line(build),
+        status: child.wait()?,
+        executables,
+        diagnostics,
+    })
+}
+
+/// Return the `cargo` subcommand to run an example with the options in
+/// `opts`, which is `miri` to run it in the Miri interpreter.
+fn run_subcommand(opts: &RunOptions) -> &'static str {
+    match opts.miri {
+        true => MIRI_SUBCOMMAND,
+        false => "run",
+    }
+}
+
+/// Check that the *nightly* toolchain is installed along with the `miri`
+/// component, so that `cargo +nightly miri run` can be called.
+pub(crate) fn check_miri_installed() -> Result<()> {
+    let succeeds = |cmd: &mut Command| {
+        let status = cmd.stdout(Stdio::null()).stderr(Stdio::null()).status();
+        matches!(status, Ok(status) if status.success())
+    };
+
+    if !succeeds(Command::new(CARGO_CMD).args(["+nightly", "--version"])) {
+        return Err(
+            "`--miri` needs the nightly toolchain, which is not installed \
+            -- install it with `rustup toolchain install nightly`"
+                .into(),
+        );
+    }
+
+    if !succeeds(Command::new(CARGO_CMD).args(["+nightly", MIRI_SUBCOMMAND, "--version"])) {
+        return Err(
+            "`--miri` needs the `miri` component, which is not installed \
+            -- install it with `rustup +nightly component add miri`"
+                .into(),
+        );
+    }
+
+    Ok(())
+}
+
+/// Return true if an example is built first, and its executable is then run
+/// directly, rather than with `cargo run`.
+///
+/// This is the case when running from a different `cwd`, so that `cargo` is
+/// still invoked from the project root -- or with a timeout or limits, so
+/// they don't apply to the `cargo build`. It's also the case when `exec` is
+/// set, i.e. to collect the compiler diagnostics from the `cargo build`.
+pub(crate) fn runs_executable(opts: &RunOptions) -> bool {
+    !opts.miri
+        && (opts.exec
+            || opts.clean_output
+            || opts.executable.is_some()
+            || !opts.wrapper.is_empty()
+            || opts.cwd.is_some()
+            || opts.timeout.is_some()
+            || opts.max_memory.is_some()
+            || opts.max_cpu_time.is_some())
+}
+
+/// Return the path to the *executable* which `cargo build` creates for an
+/// example, without building it.
+fn executable_path(
+    opts: &CommonOptions,
+    ex_type: &ExampleType,
+    root_path: &Path,
+    name: &str,
+) -> Result<PathBuf> {
+    let mut dir = target_dir(opts, ex_type, root_path);
+
+    // with a single `--target`, the artifacts are in a folder for it
+    if let [target] = opts.target.as_slice() {
+        dir.push(target);
+    }
+
+    dir.push(match opts.profile.as_deref() {
+        Some("dev") | Some("test") => "debug",
+        Some("release") | Some("bench") => "release",
+        Some(profile) => profile,
+        None if opts.release => "release",
+        None => "debug",
+    });
+
+    let file = match ex_type {
+        ExampleType::Crate(_, Some(bin)) => bin.to_owned(),
+        // the only binary target is named after the package
+        ExampleType::Crate(manifest_path, None) => {
+            Manifest::from_slice(&std::fs::read(manifest_path)?)
+                .map_err(|e| format!("failed to parse `{}`: {e}", manifest_path.display()))?
+                .package
+                .map_or_else(|| name.to_owned(), |pkg| pkg.name)
+        }
+        _ => {
+            dir.push(EXAMPLES_FOLDER);
+            name.to_owned()
+        }
+    };
+
+    Ok(dir.join(format!("{file}{}", env::consts::EXE_SUFFIX)))
+}
+
+/// Return the command to run the `executable` of an example directly, under
+/// the `wrapper` command if there is one -- i.e. a debugger.
+fn exec_command(executable: &Path, wrapper: &[String]) -> Command {
+    match wrapper.split_first() {
+        Some((program, args)) => {
+            let mut cmd = Command::new(program);
+            cmd.args(args).arg(executable);
+            cmd
+        }
+        None => Command::new(executable),
+    }
+}
+
+/// Return the `cargo` command to run an example, for [`export_example`]
+/// and [`export_cargo_command`] -- with any paths relative to the directory
+/// it's run from, which is either the project root or the `cwd` in `opts`.
+///
+/// [`export_example`]: RunExampleExt::export_example
+/// [`export_cargo_command`]: ExportCargoCommandExt::export_cargo_command
+fn export_command<T: IntoIterator>(
+    cargo: &CommonOptions,
+    ex_type: &ExampleType,
+    root_path: &Path,
+    name: &str,
+    args: T,
+    required_features: &Option<String>,
+    opts: &RunOptions,
+) -> Result<Command>
+where
+    <T as IntoIterator>::Item: AsRef<OsStr>,
+{
+    let dir = opts.cwd.as_deref().unwrap_or(root_path);
+
+    let mut run = cargo.cargo_command(
+        run_subcommand(opts),
+        ex_type,
+        root_path,
+        name,
+        required_features,
+    );
+
+    // `cargo` looks for the `Cargo.toml` in the current directory, so
+    // pass it in explicitly when running from a different directory.
+    if opts.cwd.is_some() {
+        let cmd_args: Vec<OsString> = run.get_args().map(OsStr::to_os_string).collect();
+        let mut manifest_run = Command::new(CARGO_CMD);
+
+        match ex_type {
+            ExampleType::Crate(manifest_path, _) => {
+                let mut cmd_args = cmd_args.iter();
+                // replace the path, which is relative to the project root
+                for arg in cmd_args.by_ref() {
+                    manifest_run.arg(arg);
+                    if arg == "--manifest-path" {
+                        break;
+                    }
+                }
+                cmd_args.next();
+                manifest_run
+                    .arg(relative_path(manifest_path, dir))
+                    .args(cmd_args);
+            }
+            _ => {
+                let mut cmd_args = cmd_args.iter();
+                // add it right after the `run` subcommand
+                for arg in cmd_args.by_ref() {
+                    manifest_run.arg(arg);
+                    if arg == "run" {
+                        break;
+                    }
+                }
+                manifest_run
+                    .arg("--manifest-path")
+                    .arg(relative_path(&root_path.join(CARGO_TOML), dir))
+                    .args(cmd_args);
+            }
+        }
+
+        run = manifest_run;
+    }
+
+    let placeholders = relative_placeholders(cargo, ex_type, root_path, opts);
+    let placeholders: Vec<(&str, &Path)> = placeholders
+        .iter()
+        .map(|(key, path)| (*key, path.as_path()))
+        .collect();
+
+    add_example_args(&mut run, args, &placeholders, false)?;
+
+    Ok(run)
+}
+
+/// Return the values of the placeholders in the arguments to an example,
+/// such as `{root}`, as paths relative to the directory it's run from.
+fn relative_placeholders(
+    cargo: &CommonOptions,
+    ex_type: &ExampleType,
+    root_path: &Path,
+    opts: &RunOptions,
+) -> [(&'static str, PathBuf); 3] {
+    let dir = opts.cwd.as_deref().unwrap_or(root_path);
+    [
+        ("root", relative_path(root_path, dir)),
+        ("example_dir", relative_path(&opts.example_dir, dir)),
+        (
+            "target_dir",
+            relative_path(&target_dir(cargo, ex_type, root_path), dir),
+        ),
+    ]
+}
+
+/// Return the *shell command* for the command `run` of an example, to run
+/// from the project root -- which changes to the working directory of the
+/// example (in a subshell) if needed, and sets any env variables and
+/// *stdin* in `opts`.
+fn shell_command(run: &Command, root_path: &Path, opts: &RunOptions) -> String {
+    let dir = opts.cwd.as_deref().unwrap_or(root_path);
+
+    let mut line = String::new();
+
+    // pipe in any *stdin* text with `printf`, as `<<<` is not POSIX
+    if let Some(StdinSource::Text(ref text)) = opts.stdin {
+        line.push_str(&format!(
+            "printf '%s' {} | ",
+            OsStr::new(text).shell_escape()
+        ));
+    }
+
+    for (key, value) in opts.env.iter() {
+        line.push_str(&format!("{key}={} ", OsStr::new(value).shell_escape()));
+    }
+
+    line.push_str(&command_line(run));
+
+    if let Some(StdinSource::File(ref path)) = opts.stdin {
+        let path = relative_path(path, dir);
+        line.push_str(&format!(" < {}", path.as_os_str().shell_escape()));
+    }
+
+    // change directories in a subshell, so the next command is still
+    // run from the project root
+    if let Some(ref cwd) = opts.cwd {
+        let cwd = relative_path(cwd, root_path);
+        line = format!("(cd {} && {line})", cwd.as_os_str().shell_escape());
+    }
+
+    line
+}
+
+/// Defines the logic for building the `cargo` command for an example
+trait CargoCommandExt {
+    /// Build the `cargo <subcommand>` command for an example `name`, along
+    /// with any options passed in to `rx`.
+    ///
+    /// A `subcommand` of `miri` builds the `cargo +nightly miri run`
+    /// command instead.
+    fn cargo_command(
         &self,
-        ex_type: &'a ExampleType,
-        root_path: &'a Path,
-        name: &'a str,
-        args: T,
-        required_features: &'a Option<String>,
-    ) -> Result<()>
-    where
-        <T as IntoIterator>::Item: AsRef<OsStr>,
-    {
+        subcommand: &str,
+        ex_type: &ExampleType,
+        root_path: &Path,
+        name: &str,
+        required_features: &Option<String>,
+    ) -> Command;
+}
+
+impl CargoCommandExt for CommonOptions {
+    fn cargo_command(
+        &self,
+        subcommand: &str,
+        ex_type: &ExampleType,
+        root_path: &Path,
+        name: &str,
+        required_features: &Option<String>,
+    ) -> Command {
         let mut run = Command::new(CARGO_CMD);
         run.current_dir(root_path);
 
@@ -82,14 +782,24 @@ impl RunExampleExt for CommonOptions {
 
         let has_unstable_opts = has_config || has_unstable_flags || self.unit_graph;
 
-        if !has_unstable_opts {
-            add_run_arg(&mut run, name, ex_type, root_path);
+        // `cargo miri` is only available on the nightly toolchain
+        let miri = subcommand == MIRI_SUBCOMMAND;
+
+        if !has_unstable_opts && !miri {
+            add_run_arg(&mut run, subcommand, name, ex_type, root_path);
         } else {
             // enable the `+nightly` toolchain
             run.arg("+nightly");
-            add_run_arg(&mut run, name, ex_type, root_path);
-            // enable the `unstable-options`
-            run.arg("-Z").arg("unstable-options");
+            if miri {
+                run.arg(MIRI_SUBCOMMAND);
+                add_run_arg(&mut run, "run", name, ex_type, root_path);
+            } else {
+                add_run_arg(&mut run, subcommand, name, ex_type, root_path);
+            }
+            // enable the `unstable-options`, unless they're passed in already
+            if has_unstable_opts && !self.unstable_flags.iter().any(|f| f == "unstable-options") {
+                run.arg("-Z").arg("unstable-options");
+            }
         }
 
         if self.quiet {
@@ -197,33 +907,399 @@ impl RunExampleExt for CommonOptions {
             }
         }
 
-        run.args(args);
+        run
+    }
+}
+
+impl SpawnExampleExt for CommonOptions {
+    fn spawn_example<'a, T: IntoIterator>(
+        &self,
+        ex_type: &'a ExampleType,
+        root_path: &'a Path,
+        name: &'a str,
+        args: T,
+        required_features: &'a Option<String>,
+        opts: &'a RunOptions,
+    ) -> Result<Spawned>
+    where
+        <T as IntoIterator>::Item: AsRef<OsStr>,
+    {
+        // `cargo miri run` interprets the example, so there's no executable
+        // to run directly
+        if opts.miri && opts.cwd.is_some() {
+            return Err(format!(
+                "example `{name}` can't be run with `--miri` in a different working directory"
+            )
+            .into());
+        }
+
+        let exec = runs_executable(opts);
+
+        let mut run = match exec {
+            true => {
+                let executable = match opts.executable {
+                    // Run the executable which was built up front
+                    Some(ref executable) => executable.to_owned(),
+                    // Build the example first, and then run its executable
+                    None => {
+                        let mut build_opts = self.clone();
+                        build_opts.message_format = vec![build_message_format().to_owned()];
+
+                        let mut build = build_opts.cargo_command(
+                            "build",
+                            ex_type,
+                            root_path,
+                            name,
+                            required_features,
+                        );
+
+                        match build_executable(
+                            &mut build,
+                            ex_type,
+                            root_path,
+                            name,
+                            opts.clean_output,
+                        )? {
+                            (_, Some(executable)) => executable,
+                            (output, None) => return Ok(Spawned::Exited(output)),
+                        }
+                    }
+                };
+
+                let mut run = exec_command(&executable, &opts.wrapper);
+                run.current_dir(opts.cwd.as_deref().unwrap_or(root_path));
+                add_cargo_env(
+                    &mut run,
+                    &manifest_path(self, ex_type, root_path),
+                    &executable,
+                )?;
+                run
+            }
+            false => self.cargo_command(
+                run_subcommand(opts),
+                ex_type,
+                root_path,
+                name,
+                required_features,
+            ),
+        };
+
+        run.envs(&opts.env);
+
+        if opts.capture_output {
+            run.stdout(Stdio::piped()).stderr(Stdio::piped());
+        }
+
+        configure_process(&mut run, opts)?;
 
-        // TODO: maybe it would be a better idea to use something like `shellwords::join()`
-        let cargo_run_args = run
-            .get_args()
-            .map(OsStr::display_string)
-            .collect::<Vec<_>>()
-            .join(" ");
+        // Expand any placeholders, such as `{root}`, in the arguments
+        let target_dir = target_dir(self, ex_type, root_path);
+        let placeholders = [
+            ("root", root_path),
+            ("example_dir", opts.example_dir.as_path()),
+            ("target_dir", target_dir.as_path()),
+        ];
 
-        #[cfg(target_family = "windows")]
-        println!(
-            " {} {} {}",
-            ">>".white().bold(),
-            CARGO_CMD.bright_blue().italic(),
-            cargo_run_args.as_str().bright_blue().italic()
-        );
+        // the `--` separator is only needed for `cargo run`
+        add_example_args(&mut run, args, &placeholders, exec)?;
 
-        #[cfg(not(target_family = "windows"))]
-        println!(
-            " {} {} {}",
-            "❯❯".white().bold(),
-            CARGO_CMD.blue().italic(),
-            cargo_run_args.as_str().blue().italic()
-        );
+        // Display any *stdin* we pipe in, i.e. `cargo run ... < input.txt`
+        let stdin_display = match opts.stdin {
+            Some(StdinSource::File(ref path)) => {
+                let file = File::open(path)
+                    .map_err(|e| format!("failed to open stdin file `{}`: {e}", path.display()))?;
+                run.stdin(file);
+                format!(" < {}", path.as_os_str().shell_escape())
+            }
+            Some(StdinSource::Text(ref text)) => {
+                run.stdin(Stdio::piped());
+                format!(" <<< {}", OsStr::new(text).shell_escape())
+            }
+            None => String::new(),
+        };
+
+        // Display any env variables we set, i.e. `RUST_LOG=info cargo run ...`
+        let mut prefix: String = opts
+            .env
+            .iter()
+            .map(|(key, value)| format!("{key}={} ", OsStr::new(value).shell_escape()))
+            .collect();
+
+        if let Some(ref cwd) = opts.cwd {
+            prefix.insert_str(0, &format!("cd {} && ", cwd.as_os_str().shell_escape()));
+        }
+
+        print_command(&run, &prefix, &stdin_display);
+        let command = format!("{prefix}{}{stdin_display}", command_line(&run));
+
+        let mut child = run.spawn().map_err(|e| match opts.wrapper.first() {
+            Some(program) => format!("failed to run wrapper command `{program}`: {e}").into(),
+            None => Error::from(e),
+        })?;
+        attach_process(&child);
+
+        // pipe the text in to the example's stdin, from a separate thread
+        // so that we don't block if the example doesn't read all of it.
+        if let Some(StdinSource::Text(ref text)) = opts.stdin {
+            let mut stdin = child.stdin.take().expect("Failed to open stdin");
+            let text = text.to_owned();
+            std::thread::spawn(move || {
+                // the example may exit before reading all of stdin
+                let _ = stdin.write_all(text.as_bytes());
+            });
+        }
+
+        Ok(Spawned::Running(child, command))
+    }
+}
+
+impl DryRunExampleExt for CommonOptions {
+    fn dry_run_example<'a, T: IntoIterator>(
+        &self,
+        ex_type: &'a ExampleType,
+        root_path: &'a Path,
+        name: &'a str,
+        args: T,
+        required_features: &'a Option<String>,
+        opts: &'a RunOptions,
+    ) -> Result<Vec<String>>
+    where
+        <T as IntoIterator>::Item: AsRef<OsStr>,
+    {
+        if !runs_executable(opts) {
+            let run =
+                self.export_example(ex_type, root_path, name, args, required_features, opts)?;
+            return Ok(vec![run]);
+        }
+
+        let build = self.cargo_command("build", ex_type, root_path, name, required_features);
+
+        let dir = opts.cwd.as_deref().unwrap_or(root_path);
+        let executable = executable_path(self, ex_type, root_path, name)?;
+        let mut run = exec_command(&relative_path(&executable, dir), &opts.wrapper);
+
+        let placeholders = relative_placeholders(self, ex_type, root_path, opts);
+        let placeholders: Vec<(&str, &Path)> = placeholders
+            .iter()
+            .map(|(key, path)| (*key, path.as_path()))
+            .collect();
+
+        add_example_args(&mut run, args, &placeholders, true)?;
+
+        let mut line = shell_command(&run, root_path, opts);
+
+        let limits: Vec<String> = [
+            opts.timeout.map(|timeout| format!("timeout: {timeout:?}")),
+            opts.max_memory
+                .map(|bytes| format!("max memory: {bytes} bytes")),
+            opts.max_cpu_time
+                .map(|secs| format!("max CPU time: {secs}s")),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        if !limits.is_empty() {
+            line.push_str(&format!("  # {}", limits.join(", ")));
+        }
+
+        Ok(vec![command_line(&build), line])
+    }
+}
+
+impl PrebuildExamplesExt for CommonOptions {
+    fn prebuild_examples(
+        &self,
+        root_path: &Path,
+        examples: &[(&str, &ExampleFile)],
+        clean_output: bool,
+    ) -> Result<(ExitStatus, BTreeMap<String, PathBuf>)> {
+        let mut build_opts = self.clone();
+        build_opts.message_format = vec![build_message_format().to_owned()];
+
+        // the status of the first build which failed, or else the last one
+        let mut status: Option<ExitStatus> = None;
+        let mut update_status = |build: ExitStatus| match status {
+            Some(s) if !s.success() => {}
+            _ => status = Some(build),
+        };
+        let mut executables = BTreeMap::new();
+
+        // the examples in the project are built together, with the union
+        // of their required features -- as features are additive
+        let (crates, project): (Vec<_>, Vec<_>) = examples
+            .iter()
+            .partition(|(_, ex)| matches!(ex.path_type, ExampleType::Crate(..)));
+
+        if let Some((&&(first, first_ex), rest)) = project.split_first() {
+            let mut features: Vec<&str> = project
+                .iter()
+                .filter_map(|(_, ex)| ex.required_features.as_deref())
+                .flat_map(|f| f.split(|c: char| c == ',' || c.is_whitespace()))
+                .filter(|f| !f.is_empty())
+                .collect();
+            features.sort_unstable();
+            features.dedup();
+            let features = (!features.is_empty()).then(|| features.join(" "));
+
+            let mut build =
+                build_opts.cargo_command("build", &first_ex.path_type, root_path, first, &features);
+            for (name, _) in rest {
+                build.arg("--example").arg(name);
+            }
+
+            let built = build_executables(&mut build, root_path, clean_output)?;
+            update_status(built.status);
+            for (target, exe) in built.executables {
+                let is_example = target.kind.iter().any(|k| k == "example");
+                if is_example && project.iter().any(|(name, _)| *name == target.name) {
+                    executables.insert(target.name, exe);
+                }
+            }
+        }
+
+        // a nested crate has its own `Cargo.toml`, so it's built on its own
+        for &(name, ex) in crates {
+            let mut build = build_opts.cargo_command(
+                "build",
+                &ex.path_type,
+                root_path,
+                name,
+                &ex.required_features,
+            );
+
+            let (built, executable) =
+                build_executable(&mut build, &ex.path_type, root_path, name, clean_output)?;
+            update_status(built.status);
+            if let Some(exe) = executable {
+                executables.insert(name.to_string(), exe);
+            }
+        }
+
+        let status = status.ok_or("no examples to build")?;
+
+        Ok((status, executables))
+    }
+}
+
+impl RunExampleExt for CommonOptions {
+    fn run_example<'a, T: IntoIterator>(
+        &self,
+        ex_type: &'a ExampleType,
+        root_path: &'a Path,
+        name: &'a str,
+        args: T,
+        required_features: &'a Option<String>,
+        opts: &'a RunOptions,
+    ) -> Result<ExitStatus>
+    where
+        <T as IntoIterator>::Item: AsRef<OsStr>,
+    {
+        match self.spawn_example(ex_type, root_path, name, args, required_features, opts)? {
+            Spawned::Exited(build) => Ok(build.status),
+            Spawned::Running(mut child, _) => Ok(wait_with_timeout(&mut child, opts.timeout)?.0),
+        }
+    }
+}
+
+impl BuildExampleExt for CommonOptions {
+    fn build_example<'a>(
+        &self,
+        ex_type: &'a ExampleType,
+        root_path: &'a Path,
+        name: &'a str,
+        required_features: &'a Option<String>,
+    ) -> Result<ExitStatus> {
+        let mut build = self.cargo_command("build", ex_type, root_path, name, required_features);
+        print_command(&build, "", "");
+
+        Ok(build.status()?)
+    }
+}
+
+impl ExportExampleExt for CommonOptions {
+    fn export_example<'a, T: IntoIterator>(
+        &self,
+        ex_type: &'a ExampleType,
+        root_path: &'a Path,
+        name: &'a str,
+        args: T,
+        required_features: &'a Option<String>,
+        opts: &'a RunOptions,
+    ) -> Result<String>
+    where
+        <T as IntoIterator>::Item: AsRef<OsStr>,
+    {
+        let run = export_command(
+            self,
+            ex_type,
+            root_path,
+            name,
+            args,
+            required_features,
+            opts,
+        )?;
+        Ok(shell_command(&run, root_path, opts))
+    }
+}
 
-        run.spawn()?.wait()?;
+impl ExportCargoCommandExt for CommonOptions {
+    fn export_cargo_command<'a, T: IntoIterator>(
+        &self,
+        ex_type: &'a ExampleType,
+        root_path: &'a Path,
+        name: &'a str,
+        args: T,
+        required_features: &'a Option<String>,
+        opts: &'a RunOptions,
+    ) -> Result<String>
+    where
+        <T as IntoIterator>::Item: AsRef<OsStr>,
+    {
+        let opts = RunOptions {
+            cwd: None,
+            ..opts.clone()
+        };
+        let run = export_command(
+            self,
+            ex_type,
+            root_path,
+            name,
+            args,
+            required_features,
+            &opts,
+        )?;
+
+        Ok(command_line(&run))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        Ok(())
+    #[test]
+    #[cfg(not(target_family = "windows"))]
+    fn test_relative_path() {
+        let root = Path::new("/home/me/project");
+
+        assert_eq!(relative_path(root, root), Path::new("."));
+        assert_eq!(
+            relative_path(&root.join("Cargo.toml"), &root.join("examples/nested")),
+            Path::new("../../Cargo.toml")
+        );
+        assert_eq!(
+            relative_path(&root.join("examples/input.txt"), root),
+            Path::new("examples/input.txt")
+        );
+        // only the root is in common
+        assert_eq!(
+            relative_path(Path::new("/tmp/input.txt"), root),
+            Path::new("/tmp/input.txt")
+        );
+        assert_eq!(
+            relative_path(Path::new("input.txt"), root),
+            Path::new("input.txt")
+        );
     }
 }
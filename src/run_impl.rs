@@ -1,248 +1,682 @@
 use crate::*;
-pub(crate) use inner_impl::*;
 
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::env;
+use std::process::ExitStatus;
+use std::time::Instant;
+
+use cargo_options::CommonOptions;
 use colored::Colorize;
+use path_absolutize::*;
+
+pub(crate) fn process_input_inner(
+    example_files: BTreeMap<Cow<'_, str>, ExampleFile>,
+    dir: &Paths,
+    args: Args,
+) -> Result<i32> {
+    // Command to run each example under, i.e. a debugger
+    let wrapper = wrapper_command(&args)?;
+
+    let script_args = expand_response_files(args.args)?;
+
+    // the example can also be passed in via `--example`, in case it has
+    // the same name as a subcommand
+    let example_name = args.name.or(args.example);
+
+    let root_ref = &dir.root_path;
+
+    let cfg: ReplayConfig = get_last_replay(root_ref);
+
+    // The previous run to replay, if any
+    let replay: Option<LastRun> = match args.command {
+        Some(SubCommand::History) => match select_history_entry(root_ref)? {
+            Some(entry) => Some(entry.run),
+            None => return Ok(0),
+        },
+        Some(SubCommand::Replay { number }) => Some(history_entry(root_ref, number)?.run),
+        Some(SubCommand::Cache { .. }) => unreachable!("handled in `process_input`"),
+        None if args.replay => match example_name {
+            Some(ref name) => Some(last_history_entry_for(root_ref, name)?.run),
+            None if args.global => Some(get_global_last_run()?),
+            None => Some(get_last_run(root_ref)?),
+        },
+        None => match args.run_file {
+            Some(ref path) => Some(read_run_file(path, root_ref)?),
+            None => None,
+        },
+    };
+
+    // Edit the run before replaying it, if needed
+    let replay = match replay {
+        Some(run) if args.edit => match edit_run(root_ref, run)? {
+            Some(run) => Some(run),
+            None => return Ok(0),
+        },
+        replay => replay,
+    };
+
+    let examples_to_run = if let Some(ref run) = replay {
+        run.examples()
+    } else if args.all {
+        let filter = args.filter.as_deref().unwrap_or("*");
+        example_files
+            .keys()
+            .filter(|name| glob_match(filter, name))
+            .map(|name| name.to_string())
+            .collect()
+    } else if let Some(example) = example_name {
+        vec![example]
+    } else {
+        select_examples(&example_files)?
+    };
+
+    let words: Vec<String>;
+
+    let example_args = if let Some(ref run) = replay {
+        run.arguments.iter().map(String::as_str).collect()
+    } else if !script_args.is_empty() {
+        // Build and return extra arguments to pass to the script
+        let mut extra_args = Vec::with_capacity(script_args.len() + 1);
+        extra_args.push("--");
+        for arg in script_args.iter() {
+            extra_args.push(arg);
+        }
+        extra_args
+    } else if args.input_args {
+        words = prompt_arguments(&[])?;
+        words.iter().map(String::as_str).collect()
+    } else {
+        Vec::default()
+    };
+
+    // Content to pipe in to the example's stdin, if any
+    let stdin = if let Some(path) = args.stdin {
+        Some(StdinSource::File(path.absolutize()?.into()))
+    } else if let Some(text) = args.stdin_text {
+        Some(StdinSource::Text(text))
+    } else if let Some(ref run) = replay {
+        run.stdin()
+    } else {
+        None
+    };
+
+    // Working directory to run the example in, if any
+    let (cwd, cwd_example) = match (args.cwd, &replay) {
+        (Some(path), _) => (Some(path.absolutize()?.into_owned()), false),
+        (None, Some(run)) if !args.cwd_example => (run.cwd.clone(), run.cwd_example),
+        (None, _) => (None, args.cwd_example),
+    };
+
+    // Options to pass to `cargo`; on a replay, any options passed in
+    // explicitly take precedence over the ones it was run with.
+    let cargo = match replay {
+        Some(ref run) => args.cargo.or(CommonOptions::from_args(&run.cargo_args)?),
+        None => args.cargo,
+    };
+
+    if examples_to_run.is_empty() {
+        if args.all {
+            eprintln!("{} no examples to run", "warning:".yellow().bold());
+        }
+        return Ok(0);
+    }
+
+    if args.watch && examples_to_run.len() > 1 {
+        return Err("`--watch` can only be used with a single example".into());
+    }
 
-#[cfg(target_family = "windows")]
-mod inner_impl {
-    use super::*;
-
-    use std::borrow::Cow;
-    use std::collections::BTreeMap;
-    use std::io::Write;
-    use std::process::{Command, Output, Stdio};
-
-    //noinspection DuplicatedCode
-    pub(crate) fn process_input_inner(
-        example_files: BTreeMap<Cow<'_, str>, ExampleFile>,
-        dir: &Paths,
-        args: Args,
-    ) -> Result<()> {
-        let script_args = args.args;
-        let mut cfg: ReplayConfig = Default::default();
-        let output: Output;
-
-        let examples_to_run = if args.replay {
-            cfg = get_last_replay()?;
-            vec![Cow::Owned(cfg.last_run.name)]
-        } else if let Some(example) = args.name {
-            vec![Cow::Owned(example)]
+    let example_args_ref = &example_args;
+
+    // Save info on the examples we're running, so we can `--replay` them if needed
+    let mut last_run = LastRun {
+        cargo_args: cargo.to_args(),
+        cwd: cwd.clone(),
+        cwd_example,
+        ..LastRun::new(&examples_to_run, example_args_ref, stdin.as_ref())
+    };
+    // a smoke test with `--all` (or a dry run) is not saved as the last run
+    let save_run = args.export.is_none() && !args.all && !args.dry_run;
+    if save_run {
+        warn_on_cache_error(save_last_replay(root_ref, last_run.clone()));
+    }
+
+    // keep going on failures when running all examples, so that we get a
+    // full report at the end
+    let keep_going = args.keep_going || args.all;
+
+    // Check that Miri is installed, before running any examples with it
+    if args.miri && !args.dry_run && args.export.is_none() {
+        check_miri_installed()?;
+    }
+
+    // Build all examples up front, if needed
+    let prebuilt = if args.prebuild {
+        let examples = examples_to_run
+            .iter()
+            .map(|name| match example_files.get(name.as_str()) {
+                Some(example) => Ok((name.as_str(), example)),
+                None => Err(format!("no example named `{name}` in this project").into()),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Some(cargo.prebuild_examples(root_ref, &examples, args.clean_output)?)
+    } else {
+        None
+    };
+
+    // Offer to jump to the compiler errors of an example which fails to
+    // build, if the user is at a terminal
+    let jump_to_errors = !args.all
+        && prebuilt.is_none()
+        && atty::is(atty::Stream::Stdin)
+        && atty::is(atty::Stream::Stdout);
+
+    let timestamp = unix_timestamp();
+    let mut results = Vec::with_capacity(examples_to_run.len());
+    let mut exported = Vec::new();
+    // Details on each example for the reports, if any
+    let mut report_entries = Vec::new();
+    // Exit codes of any examples which failed
+    let mut failures = Vec::new();
+
+    for name in examples_to_run.iter().map(String::as_str) {
+        let example = example_files
+            .get(name)
+            .ok_or_else(|| format!("no example named `{name}` in this project"))?;
+        let metadata = dir.example_metadata(name)?;
+
+        let example_dir = example.dir();
+
+        let cwd = if cwd_example {
+            Some(example_dir.clone())
         } else {
-            let example_names: String = example_files
-                .keys()
-                .map(|k| k.as_ref())
-                .collect::<Vec<_>>()
-                .join("\n");
-
-            // I was previously testing with the `echo` command -- i.e. the
-            // equivalent of `echo "one\ntwo\nthree" | fzf` -- however this is
-            // not needed anymore, as I realized we can pipe stdin directly;
-            // see below.
-
-            let mut child = Command::new(FZF_CMD)
-                .stdin(Stdio::piped())
-                .stdout(Stdio::piped())
-                // .stderr(Stdio::piped())
-                .arg("-m")
-                .spawn()
-                .expect("Failed to spawn child process");
-
-            // pipe stdin in to the `fzf` command
-            let mut stdin = child.stdin.take().expect("Failed to open stdin");
-            std::thread::spawn(move || {
-                stdin
-                    .write_all(example_names.as_bytes())
-                    .expect("Failed to write to stdin");
+            match cwd {
+                Some(ref cwd) => Some(cwd.clone()),
+                None => metadata.cwd(root_ref)?,
+            }
+        };
+
+        // Only build the example, if needed
+        if args.build_only {
+            let start = Instant::now();
+            let status = cargo.build_example(
+                &example.path_type,
+                root_ref,
+                name,
+                &example.required_features,
+            )?;
+            results.push(ExampleResult {
+                name: name.to_owned(),
+                duration: start.elapsed().as_secs_f64(),
+                exit_code: status.code(),
+                output_mismatch: false,
+                timed_out: false,
             });
+            if !status.success() {
+                failures.push(exit_code(&status));
+            }
+            continue;
+        }
 
-            // get the output from running `fzf`
-            output = child.wait_with_output().expect("Failed to read stdout");
+        let mut opts = RunOptions {
+            stdin: match stdin {
+                Some(ref stdin) => Some(stdin.clone()),
+                None => metadata.stdin_source(root_ref)?,
+            },
+            example_dir,
+            // no need to change directories if it's the project root
+            cwd: cwd.filter(|cwd| cwd != root_ref),
+            executable: prebuilt
+                .as_ref()
+                .and_then(|(_, executables)| executables.get(name).cloned()),
+            timeout: match args.timeout {
+                Some(timeout) => Some(timeout),
+                None => metadata
+                    .timeout()
+                    .map_err(|e| format!("example `{name}` in `{CARGO_TOML}`: {e}"))?,
+            },
+            clean_output: args.clean_output,
+            max_memory: args.max_memory,
+            // round up to the next second, as that's the granularity of the limit
+            max_cpu_time: args.max_cpu_time.map(|d| d.as_secs_f64().ceil() as u64),
+            wrapper: wrapper.clone(),
+            miri: args.miri,
+            ..Default::default()
+        };
 
-            std::str::from_utf8(&output.stdout)?
-                .split_terminator('\n')
-                .map(Cow::Borrowed)
-                .collect()
+        // Env variables the example was run with, if it's a replay
+        let replay_env = replay.as_ref().and_then(|run| run.env.get(name));
+
+        // Select the log level to run the example with, or else re-use the
+        // one last selected -- unless `RUST_LOG` is already set. A dry run,
+        // an export, or a run of all examples re-uses the last selection, so
+        // that nothing is prompted or saved.
+        let log_settings = if args.log && !args.dry_run && args.export.is_none() && !args.all {
+            let settings = select_log_settings(dir, name)?;
+            warn_on_cache_error(save_log_settings(root_ref, name, &settings));
+            Some(settings)
+        } else if let Some(replay_env) = replay_env {
+            // any env variables which are already set take precedence
+            for (key, value) in replay_env {
+                if env::var_os(key).is_none() {
+                    opts.env.insert(key.to_owned(), value.to_owned());
+                }
+            }
+            None
+        } else if env::var_os(RUST_LOG).is_none() {
+            cfg.log_settings.get(name).cloned()
+        } else {
+            None
         };
 
-        let words: Vec<String>;
+        // Pass through any flags for Miri, so they're shown in the command
+        // and saved for a `--replay`
+        if args.miri {
+            if let Ok(flags) = env::var(MIRIFLAGS) {
+                opts.env.insert(MIRIFLAGS.to_owned(), flags);
+            }
+        }
 
-        let example_args = if args.replay {
-            cfg.last_run.arguments.iter().map(String::as_str).collect()
-        } else if !script_args.is_empty() {
-            // Build and return extra arguments to pass to the script
-            let mut extra_args = Vec::with_capacity(script_args.len() + 1);
-            extra_args.push("--");
-            for arg in script_args.iter() {
-                extra_args.push(arg);
+        if let Some(settings) = log_settings {
+            if let Some(rust_log) = settings.rust_log {
+                opts.env.insert(RUST_LOG.to_owned(), rust_log);
             }
-            extra_args
-        } else if args.input_args {
-            // Print label for input
-            print!("{} ", "Arguments:".cyan().bold());
-            std::io::stdout().flush()?;
-            // Read user input
-            let mut line = String::new();
-            std::io::stdin().read_line(&mut line)?;
-            // Split user-entered arguments, respecting quotes and other characters
-            words = shellwords::split(line.trim_end())?;
-            // Build and return extra arguments to pass to the script
-            let mut extra_args = Vec::with_capacity(words.len() + 1);
-            extra_args.push("--");
-            for arg in words.iter() {
-                extra_args.push(arg);
+            if let Some(rust_backtrace) = settings.rust_backtrace {
+                opts.env.insert(RUST_BACKTRACE.to_owned(), rust_backtrace);
             }
-            extra_args
-        } else {
-            Vec::default()
-        };
+        }
 
-        let example_args_ref = &example_args;
-        let root_ref = &dir.root_path;
+        // Rerun the example whenever its files change, until interrupted
+        if args.watch {
+            watch_example(
+                &cargo,
+                example,
+                root_ref,
+                name,
+                example_args_ref,
+                &opts,
+                &args.watch_ignore,
+            )?;
+            return Ok(0);
+        }
 
-        // Save info on the example we're running, so we can `--replay` it if needed
-        match examples_to_run.first() {
-            Some(name) if !args.replay => {
-                save_last_replay(name, example_args_ref)?;
+        // When running all examples, pass in the preset arguments for each
+        // one, and don't wait on input from the terminal.
+        let preset_args: Vec<&str>;
+        let example_args = if args.all {
+            if opts.stdin.is_none() {
+                opts.stdin = Some(StdinSource::Text(String::new()));
             }
-            _ => {}
+            preset_args = std::iter::once("--")
+                .chain(metadata.args.iter().map(String::as_str))
+                .collect();
+            &preset_args
+        } else {
+            example_args_ref
         };
 
-        for example_name in examples_to_run {
-            let name = example_name.as_ref();
-            let example = example_files.get(name).unwrap();
+        // Export the command rather than running it, if needed
+        if let Some(format) = args.export {
+            exported.push(match format {
+                ExportFormat::Cargo => cargo.export_cargo_command(
+                    &example.path_type,
+                    root_ref,
+                    name,
+                    example_args,
+                    &example.required_features,
+                    &opts,
+                )?,
+                _ => cargo.export_example(
+                    &example.path_type,
+                    root_ref,
+                    name,
+                    example_args,
+                    &example.required_features,
+                    &opts,
+                )?,
+            });
+            if !opts.env.is_empty() {
+                last_run.env.insert(name.to_owned(), opts.env);
+            }
+            continue;
+        }
+
+        // `cargo run` doesn't emit the compiler diagnostics as JSON, so build
+        // the example and run its executable directly, to jump to any errors.
+        // Miri interprets the example, so there's nothing to build up front.
+        if jump_to_errors && !opts.miri {
+            opts.exec = true;
+        }
 
-            // Run the Cargo example script
-            args.cargo.run_example(
+        // Print the command rather than running it, if needed
+        if args.dry_run {
+            for line in cargo.dry_run_example(
                 &example.path_type,
                 root_ref,
                 name,
-                example_args_ref,
+                example_args,
                 &example.required_features,
-            )?;
+                &opts,
+            )? {
+                println!("{line}");
+            }
+            continue;
         }
 
-        Ok(())
-    }
-}
+        // Capture the output of the example, for the snapshots or reports
+        if args.check_output || !args.report.is_empty() {
+            opts.capture_output = true;
+        }
+        // Run the executable directly, so `cargo` doesn't write to stderr
+        if args.check_output {
+            opts.exec = true;
+        }
 
-#[cfg(not(target_family = "windows"))]
-mod inner_impl {
-    use super::*;
-
-    use std::borrow::Cow;
-    use std::collections::BTreeMap;
-    use std::io::Write;
-    use std::sync::Arc;
-
-    use skim::prelude::*;
-
-    //noinspection DuplicatedCode
-    pub(crate) fn process_input_inner(
-        example_files: BTreeMap<Cow<'_, str>, ExampleFile>,
-        dir: &Paths,
-        args: Args,
-    ) -> Result<()> {
-        let script_args = args.args;
-        let selected_items: Vec<Arc<dyn SkimItem>>;
-        let mut cfg: ReplayConfig = Default::default();
-
-        let examples_to_run = if args.replay {
-            cfg = get_last_replay()?;
-            vec![Cow::Owned(cfg.last_run.name)]
-        } else if let Some(example) = args.name {
-            vec![Cow::Owned(example)]
-        } else {
-            let options = SkimOptionsBuilder::default()
-                // .height(Some("50%"))
-                .preview_window(Some("right:70%"))
-                .multi(true)
-                .preview(Some("")) // preview should be specified to enable preview window
-                .build()
-                .unwrap();
-
-            let (tx_item, rx_item): (SkimItemSender, SkimItemReceiver) = unbounded();
-
-            for example in example_files.values() {
-                let _ = tx_item.send(Arc::new(ExampleFileItem {
-                    file_stem: example.name.clone(),
-                    file_path: example.path.clone(),
-                }));
+        // Run the Cargo example script -- and if it fails to build, offer to
+        // jump to the compiler errors, then run it again once they're fixed.
+        let (output, built, duration, command) = loop {
+            let start = Instant::now();
+
+            let spawned = match prebuilt {
+                // the example was not built, as the build up front failed
+                Some((status, _)) if opts.executable.is_none() => {
+                    if status.success() {
+                        return Err(format!(
+                            "could not locate the executable for example `{name}`"
+                        )
+                        .into());
+                    }
+                    Spawned::Exited(BuildOutput {
+                        command: String::new(),
+                        status,
+                        executables: Vec::new(),
+                        diagnostics: Vec::new(),
+                    })
+                }
+                _ => cargo.spawn_example(
+                    &example.path_type,
+                    root_ref,
+                    name,
+                    example_args,
+                    &example.required_features,
+                    &opts,
+                )?,
+            };
+            // the command which was run, for the reports
+            let (output, diagnostics, command) = match spawned {
+                Spawned::Running(child, command) if opts.capture_output => {
+                    // keep the full output only if it's compared to a snapshot
+                    let limit = match args.check_output {
+                        true => None,
+                        false => Some(TAIL_BYTES),
+                    };
+                    let output = wait_with_output(child, limit, opts.timeout)?;
+                    (output, None, command)
+                }
+                Spawned::Running(mut child, command) => {
+                    let (status, timed_out) = wait_with_timeout(&mut child, opts.timeout)?;
+                    let output = CapturedOutput {
+                        timed_out,
+                        ..CapturedOutput::empty(status)
+                    };
+                    (output, None, command)
+                }
+                Spawned::Exited(build) => (
+                    CapturedOutput::empty(build.status),
+                    Some(build.diagnostics),
+                    build.command,
+                ),
+            };
+            let duration = start.elapsed().as_secs_f64();
+
+            let built = diagnostics.is_none();
+
+            if let Some(diagnostics) = diagnostics.filter(|_| jump_to_errors) {
+                if jump_to_diagnostic(root_ref, name, diagnostics)? {
+                    continue;
+                }
             }
-            drop(tx_item); // so that skim could know when to stop waiting for more items.
 
-            selected_items = Skim::run_with(&options, Some(rx_item))
-                .map(|out| {
-                    // Quit, if user presses a key such as Esc, Backspace, or Delete
-                    if out.is_abort {
-                        std::process::exit(0);
-                    }
-                    out.selected_items
-                })
-                .unwrap_or_else(Vec::new);
-
-            selected_items
-                .iter()
-                .map(|item| item.text())
-                .collect::<Vec<_>>()
+            break (output, built, duration, command);
         };
+        let status = output.status;
+        let timed_out = output.timed_out;
+        // the signal which interrupted the example, i.e. from Ctrl-C
+        let interrupt = interrupt_signal(&status);
+
+        if timed_out {
+            eprintln!(
+                "{} example `{name}` timed out after {:?}, and was killed",
+                "error:".red().bold(),
+                opts.timeout.unwrap_or_default(),
+            );
+        }
 
-        let words: Vec<String>;
-
-        let example_args = if args.replay {
-            cfg.last_run.arguments.iter().map(String::as_str).collect()
-        } else if !script_args.is_empty() {
-            // Build and return extra arguments to pass to the script
-            let mut extra_args = Vec::with_capacity(script_args.len() + 1);
-            extra_args.push("--");
-            for arg in script_args.iter() {
-                extra_args.push(arg);
-            }
-            extra_args
-        } else if args.input_args {
-            // Print label for input
-            print!("{} ", "Arguments:".cyan().bold());
-            std::io::stdout().flush()?;
-            // Read user input
-            let mut line = String::new();
-            std::io::stdin().read_line(&mut line)?;
-            // Split user-entered arguments, respecting quotes and other characters
-            words = shellwords::split(line.trim_end())?;
-            // Build and return extra arguments to pass to the script
-            let mut extra_args = Vec::with_capacity(words.len() + 1);
-            extra_args.push("--");
-            for arg in words.iter() {
-                extra_args.push(arg);
+        // Compare the output against the snapshots, if the example was built
+        // and ran to completion
+        let output_mismatch = if args.check_output && built && !timed_out {
+            // only the output of a successful run is blessed as the snapshot
+            let bless = args.bless && status.success();
+            if args.bless && !bless {
+                eprintln!(
+                    "{} not blessing the snapshots for example `{name}`, as it exited with {status}",
+                    "warning:".yellow().bold(),
+                );
             }
-            extra_args
+            let normalizer = Normalizer::new(root_ref, args.redact.iter().chain(&metadata.redact))?;
+            !check_snapshots(&dir.examples_path, name, &output, &normalizer, bless)?
         } else {
-            Vec::default()
+            false
         };
 
-        let example_args_ref = &example_args;
-        let root_ref = &dir.root_path;
+        if !args.report.is_empty() {
+            report_entries.push(ReportEntry {
+                name: name.to_owned(),
+                command,
+                outcome: match status.success() && !output_mismatch && !timed_out {
+                    true => Outcome::Passed,
+                    false => Outcome::Failed,
+                },
+                exit_code: status.code(),
+                status: Some(if timed_out {
+                    format!("timed out after {:?}", opts.timeout.unwrap_or_default())
+                } else if output_mismatch {
+                    "output does not match the snapshot".to_owned()
+                } else {
+                    status.to_string()
+                }),
+                duration,
+                stdout: output_tail(&output.stdout),
+                stderr: output_tail(&output.stderr),
+            });
+        }
+
+        if !opts.env.is_empty() {
+            last_run.env.insert(name.to_owned(), opts.env);
+        }
 
-        // Save info on the example we're running, so we can `--replay` it if needed
-        match examples_to_run.first() {
-            Some(name) if !args.replay => {
-                save_last_replay(name, example_args_ref)?;
+        results.push(ExampleResult {
+            name: name.to_owned(),
+            duration,
+            exit_code: status.code(),
+            output_mismatch,
+            timed_out,
+        });
+
+        // After an interrupt, ask whether to go on with the remaining examples
+        if let Some(signal) = interrupt {
+            failures.push(128 + signal);
+
+            let remaining = examples_to_run.len() - results.len();
+            if remaining == 0 {
+                break;
             }
-            _ => {}
-        };
+            if signal == SIGINT
+                && atty::is(atty::Stream::Stdin)
+                && confirm(
+                    &format!("Example `{name}` was interrupted; run the {remaining} remaining example(s)?"),
+                    false,
+                )?
+            {
+                continue;
+            }
+            eprintln!(
+                "{} example `{name}` was interrupted, skipping {remaining} remaining example(s)",
+                "error:".red().bold(),
+            );
+            report_entries.extend(
+                examples_to_run[results.len()..]
+                    .iter()
+                    .map(|name| ReportEntry::skipped(name, SKIPPED_INTERRUPTED)),
+            );
+            break;
+        }
 
-        for example_name in examples_to_run {
-            let name = example_name.as_ref();
-            let example = example_files.get(name).unwrap();
+        if !status.success() || output_mismatch || timed_out {
+            failures.push(if timed_out {
+                EXIT_CODE_TIMEOUT
+            } else if status.success() {
+                1
+            } else {
+                exit_code(&status)
+            });
 
-            // Run the Cargo example script
-            args.cargo.run_example(
-                &example.path_type,
-                root_ref,
-                name,
-                example_args_ref,
-                &example.required_features,
-            )?;
+            let remaining = examples_to_run.len() - results.len();
+            if remaining > 0 && !keep_going {
+                eprintln!(
+                    "{} example `{name}` failed, skipping {remaining} remaining example(s) \
+                    -- pass `--keep-going` to run them",
+                    "error:".red().bold(),
+                );
+                report_entries.extend(
+                    examples_to_run[results.len()..]
+                        .iter()
+                        .map(|name| ReportEntry::skipped(name, SKIPPED_FAILED)),
+                );
+                break;
+            }
+        }
+    }
+
+    if let Some(format) = args.export {
+        print_export(format, root_ref, &last_run, &exported)?;
+        return Ok(0);
+    }
+
+    if args.dry_run {
+        return Ok(0);
+    }
+
+    if args.all || (keep_going && results.len() > 1) {
+        print_summary(&results);
+    }
+
+    if !args.report.is_empty() {
+        write_reports(&args.report, dir, &report_entries, timestamp)?;
+    }
+
+    if !save_run {
+        return Ok(failures.first().copied().unwrap_or_default());
+    }
+
+    // Save the env variables the examples were run with, for the next `--replay`
+    if !last_run.env.is_empty() {
+        warn_on_cache_error(save_last_replay(root_ref, last_run.clone()));
+    }
+
+    // Record the run in the history, so we can `replay` it later on
+    warn_on_cache_error(append_history(
+        root_ref,
+        &HistoryEntry {
+            run: last_run,
+            results,
+            timestamp,
+        },
+    ));
+
+    // exit with the status of the first example which failed, if any
+    Ok(failures.first().copied().unwrap_or_default())
+}
+
+/// Show a warning if the *local cache* couldn't be updated, rather than
+/// aborting the run -- as the cache is only needed for a later `--replay`.
+fn warn_on_cache_error(result: Result<()>) {
+    if let Err(e) = result {
+        eprintln!(
+            "{} failed to update the local cache: {e}",
+            "warning:".yellow().bold()
+        );
+    }
+}
+
+/// Return the *wrapper* command to run each example under, if one is
+/// passed in -- i.e. via `--wrap` or `--gdb`.
+fn wrapper_command(args: &Args) -> Result<Vec<String>> {
+    let wrapper = match args.wrap {
+        Some(ref cmd) => cmd.as_str(),
+        None if args.gdb => GDB_WRAPPER,
+        None if args.lldb => LLDB_WRAPPER,
+        None if args.valgrind => VALGRIND_WRAPPER,
+        None if args.strace => STRACE_WRAPPER,
+        None if args.perf => PERF_WRAPPER,
+        None => return Ok(Vec::new()),
+    };
+
+    let words = shellwords::split(wrapper)?;
+    if words.is_empty() {
+        return Err("`--wrap` needs a command to run the example under".into());
+    }
+
+    Ok(words)
+}
+
+/// Exit code for an example which timed out, as with the `timeout` command
+const EXIT_CODE_TIMEOUT: i32 = 124;
+
+/// Return the *exit code* for the exit `status` of an example, which is
+/// `128 + signal` if it was killed by a signal -- as in the shell.
+fn exit_code(status: &ExitStatus) -> i32 {
+    #[cfg(not(target_family = "windows"))]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
         }
+    }
 
-        Ok(())
+    status.code().unwrap_or(1)
+}
+
+/// Print a *summary* of the `results` of running each example.
+fn print_summary(results: &[ExampleResult]) {
+    let failed = results.iter().filter(|r| !r.success()).count();
+    let passed = results.len() - failed;
+
+    println!();
+    println!(
+        "{} {} passed, {} failed",
+        "Summary:".cyan().bold(),
+        passed.to_string().green(),
+        failed.to_string().red()
+    );
+
+    let width = results
+        .iter()
+        .map(|r| r.name.len())
+        .max()
+        .unwrap_or_default();
+
+    for result in results {
+        let status = match result.exit_code {
+            _ if result.timed_out => "timeout".red(),
+            Some(0) if result.output_mismatch => "mismatch".red(),
+            Some(0) => "ok".green(),
+            Some(code) => format!("exit {code}").red(),
+            None => "killed".red(),
+        };
+        println!(
+            "  {:<width$}  {:<8}  {:.2}s",
+            result.name, status, result.duration
+        );
     }
 }
@@ -0,0 +1,321 @@
+//! Logic for *snapshot testing* of examples via `--check-output`, which
+//! compares the output of an example against a checked-in snapshot file,
+//! such as `examples/<name>.stdout`.
+//!
+use crate::*;
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use colored::Colorize;
+use regex::Regex;
+
+/// Pattern for *timestamps* in the output, such as `2023-03-13T10:00:00Z`
+const TIMESTAMP_PATTERN: &str =
+    r"\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:?\d{2})?";
+
+/// Number of unchanged lines to show around each change in a diff
+const DIFF_CONTEXT: usize = 3;
+
+/// Max number of lines (expected times actual) to compute a full diff for
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// Normalizes the output of an example, so that it can be compared with a
+/// snapshot across machines and runs.
+pub(crate) struct Normalizer {
+    /// Paths to replace with a placeholder, such as `[ROOT]`
+    paths: Vec<(String, &'static str)>,
+
+    /// Patterns to replace with a placeholder, such as `[TIMESTAMP]`
+    patterns: Vec<(Regex, &'static str)>,
+}
+
+impl Normalizer {
+    /// Create a `Normalizer` for an example in the Cargo project at `root`,
+    /// which also redacts any matches for the regex patterns in `redact`.
+    pub fn new<'a, I: IntoIterator<Item = &'a String>>(root: &Path, redact: I) -> Result<Self> {
+        let mut paths = vec![(root.to_string_lossy().into_owned(), "[ROOT]")];
+        if let Some(home) = home::home_dir() {
+            paths.push((home.to_string_lossy().into_owned(), "[HOME]"));
+        }
+        // replace the longest paths first, as the home folder usually
+        // contains the project root
+        paths.retain(|(path, _)| path.len() > 1);
+        paths.sort_by_key(|(path, _)| std::cmp::Reverse(path.len()));
+
+        let mut patterns = vec![(Regex::new(TIMESTAMP_PATTERN).unwrap(), "[TIMESTAMP]")];
+        for pattern in redact {
+            let regex = Regex::new(pattern)
+                .map_err(|e| format!("invalid pattern to redact `{pattern}`: {e}"))?;
+            patterns.push((regex, "[REDACTED]"));
+        }
+
+        Ok(Self { paths, patterns })
+    }
+
+    /// Return the normalized `output`.
+    pub fn normalize(&self, output: &str) -> String {
+        let mut output = output.replace("\r\n", "\n");
+
+        for (path, placeholder) in self.paths.iter() {
+            output = output.replace(path.as_str(), placeholder);
+        }
+
+        for (regex, placeholder) in self.patterns.iter() {
+            output = regex.replace_all(&output, *placeholder).into_owned();
+        }
+
+        output
+    }
+}
+
+/// Compare the captured `output` of an example `name` against its snapshot
+/// files in `dir`, which are `<name>.stdout` and `<name>.stderr`.
+///
+/// A diff is printed for any output which doesn't match its snapshot. The
+/// `.stderr` snapshot is optional, as long as the example writes nothing
+/// to *stderr*.
+///
+/// If `bless` is true, the snapshots are updated with the output instead.
+///
+/// # Returns
+/// Returns true if the output matches the snapshots.
+pub(crate) fn check_snapshots(
+    dir: &Path,
+    name: &str,
+    output: &CapturedOutput,
+    normalizer: &Normalizer,
+    bless: bool,
+) -> Result<bool> {
+    let mut matches = true;
+
+    for (stream, actual) in [("stdout", &output.stdout), ("stderr", &output.stderr)] {
+        let path = dir.join(format!("{name}.{stream}"));
+        let actual = normalizer.normalize(&String::from_utf8_lossy(actual));
+
+        let expected = match fs::read_to_string(&path) {
+            Ok(expected) => Some(expected.replace("\r\n", "\n")),
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => return Err(format!("failed to read `{}`: {e}", path.display()).into()),
+        };
+
+        if bless {
+            bless_snapshot(&path, stream, expected.as_deref(), &actual)?;
+            continue;
+        }
+
+        match expected {
+            Some(ref expected) if *expected == actual => {}
+            // an example which writes nothing to stderr doesn't need a snapshot
+            None if stream == "stderr" && actual.is_empty() => {}
+            None => {
+                matches = false;
+                eprintln!(
+                    "{} no snapshot for the {stream} of example `{name}` at `{}`",
+                    "error:".red().bold(),
+                    path.display()
+                );
+            }
+            Some(ref expected) => {
+                matches = false;
+                eprintln!(
+                    "{} the {stream} of example `{name}` does not match `{}`",
+                    "error:".red().bold(),
+                    path.display()
+                );
+                print_diff(expected, &actual);
+            }
+        }
+    }
+
+    if !bless {
+        if matches {
+            println!(
+                "{} output of example `{name}` matches the snapshot",
+                "Snapshot:".cyan().bold()
+            );
+        } else {
+            eprintln!(
+                "{} pass `--bless` to update the snapshot with the output",
+                "note:".cyan().bold()
+            );
+        }
+    }
+
+    Ok(matches)
+}
+
+/// Update the snapshot at `path` for an output `stream` with the `actual`
+/// output, if it differs from the `expected` output.
+fn bless_snapshot(path: &Path, stream: &str, expected: Option<&str>, actual: &str) -> Result<()> {
+    if expected == Some(actual) {
+        return Ok(());
+    }
+
+    // remove a stale snapshot, if the example no longer writes to stderr
+    if stream == "stderr" && actual.is_empty() {
+        if expected.is_some() {
+            fs::remove_file(path)?;
+            println!("{} removed `{}`", "Snapshot:".cyan().bold(), path.display());
+        }
+        return Ok(());
+    }
+
+    fs::write(path, actual).map_err(|e| format!("failed to write `{}`: {e}", path.display()))?;
+    println!("{} updated `{}`", "Snapshot:".cyan().bold(), path.display());
+
+    Ok(())
+}
+
+/// A line in a *diff* between the expected and actual output.
+#[derive(Debug, PartialEq, Eq)]
+enum DiffLine<'a> {
+    /// A line which is in both
+    Same(&'a str),
+    /// A line which is only in the expected output
+    Removed(&'a str),
+    /// A line which is only in the actual output
+    Added(&'a str),
+}
+
+/// Return a line-by-line *diff* between the `expected` and `actual` output,
+/// based on their longest common subsequence of lines.
+fn diff_lines<'a>(expected: &'a str, actual: &'a str) -> Vec<DiffLine<'a>> {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+    let (n, m) = (old.len(), new.len());
+
+    // fall back to replacing all lines, for very large outputs
+    if n * m > MAX_DIFF_CELLS {
+        return old
+            .into_iter()
+            .map(DiffLine::Removed)
+            .chain(new.into_iter().map(DiffLine::Added))
+            .collect();
+    }
+
+    // lcs[i][j] is the length of the LCS of `old[i..]` and `new[j..]`
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            diff.push(DiffLine::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+    diff.extend(old[i..].iter().map(|line| DiffLine::Removed(line)));
+    diff.extend(new[j..].iter().map(|line| DiffLine::Added(line)));
+
+    diff
+}
+
+/// Print a colored *diff* between the `expected` and `actual` output, with
+/// a few lines of context around each change.
+fn print_diff(expected: &str, actual: &str) {
+    let diff = diff_lines(expected, actual);
+
+    // show unchanged lines only if they're close to a change
+    let is_change = |line: &DiffLine<'_>| !matches!(line, DiffLine::Same(_));
+    let near_change = |idx: usize| {
+        let start = idx.saturating_sub(DIFF_CONTEXT);
+        let end = (idx + DIFF_CONTEXT + 1).min(diff.len());
+        diff[start..end].iter().any(is_change)
+    };
+
+    eprintln!("{}", "--- expected".red());
+    eprintln!("{}", "+++ actual".green());
+
+    let mut skipped = false;
+    for (idx, line) in diff.iter().enumerate() {
+        match line {
+            DiffLine::Removed(line) => eprintln!("{}", format!("-{line}").red()),
+            DiffLine::Added(line) => eprintln!("{}", format!("+{line}").green()),
+            DiffLine::Same(line) if near_change(idx) => {
+                skipped = false;
+                eprintln!(" {}", line.dimmed());
+            }
+            DiffLine::Same(_) => {
+                if !skipped {
+                    eprintln!("{}", "...".dimmed());
+                }
+                skipped = true;
+            }
+        }
+    }
+
+    // the output differs only in a trailing newline
+    if expected.ends_with('\n') != actual.ends_with('\n') {
+        eprintln!("{}", "(the output differs in a trailing newline)".dimmed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use DiffLine::*;
+
+    #[test]
+    fn test_normalize() {
+        let root = Path::new("/nonexistent/rx-project");
+        let normalizer = Normalizer::new(root, &[]).unwrap();
+
+        assert_eq!(
+            normalizer.normalize("reading /nonexistent/rx-project/examples/in.txt\r\n"),
+            "reading [ROOT]/examples/in.txt\n"
+        );
+        assert_eq!(
+            normalizer
+                .normalize("started at 2023-03-13T10:00:00.123Z, done 2023-03-13 10:00:05+01:00"),
+            "started at [TIMESTAMP], done [TIMESTAMP]"
+        );
+        assert_eq!(normalizer.normalize("unchanged"), "unchanged");
+    }
+
+    #[test]
+    fn test_normalize_redact() {
+        let redact = [r"id=\d+".to_owned(), "token-[a-f0-9]+".to_owned()];
+        let normalizer = Normalizer::new(Path::new("/nonexistent/rx-project"), &redact).unwrap();
+
+        assert_eq!(
+            normalizer.normalize("id=42 uses token-deadbeef"),
+            "[REDACTED] uses [REDACTED]"
+        );
+    }
+
+    #[test]
+    fn test_normalize_invalid_redact() {
+        let redact = ["(unclosed".to_owned()];
+        assert!(Normalizer::new(Path::new("/nonexistent/rx-project"), &redact).is_err());
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        assert_eq!(diff_lines("a\nb\n", "a\nb\n"), [Same("a"), Same("b")]);
+        assert_eq!(
+            diff_lines("a\nb\nc\n", "a\nx\nc\nd\n"),
+            [Same("a"), Removed("b"), Added("x"), Same("c"), Added("d")]
+        );
+        assert_eq!(diff_lines("", "a\n"), [Added("a")]);
+        assert_eq!(diff_lines("a\n", ""), [Removed("a")]);
+    }
+}
@@ -0,0 +1,228 @@
+//! Logic for *watch mode* via `--watch`, which reruns an example when its
+//! source files change.
+//!
+//! Files are watched by polling their modification times, so that no
+//! platform-specific file notification APIs are needed.
+//!
+use crate::*;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::{Duration, SystemTime};
+
+use cargo_options::CommonOptions;
+use colored::Colorize;
+
+/// How often to check the watched files for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How long to wait for changes to settle, before rerunning the example
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Files and folders which are never watched
+const DEFAULT_IGNORE: &[&str] = &["target", ".git", "*.swp", "*~", ".#*", "#*#", ".DS_Store"];
+
+/// Watches a set of files and folders for changes.
+struct Watcher {
+    /// Files and folders to watch; folders are watched recursively
+    paths: Vec<PathBuf>,
+
+    /// Glob patterns for file or folder names to ignore
+    ignore: Vec<String>,
+
+    /// Modification time and size of each file, as of the last check
+    snapshot: BTreeMap<PathBuf, (SystemTime, u64)>,
+}
+
+impl Watcher {
+    /// Create a `Watcher` for the `paths`, ignoring any file or folder
+    /// names which match a glob pattern in `ignore`.
+    fn new(paths: Vec<PathBuf>, ignore: &[String]) -> Self {
+        let ignore = DEFAULT_IGNORE
+            .iter()
+            .map(|&p| p.to_owned())
+            .chain(ignore.iter().cloned())
+            .collect();
+
+        let mut watcher = Self {
+            paths,
+            ignore,
+            snapshot: BTreeMap::new(),
+        };
+        watcher.snapshot = watcher.scan();
+        watcher
+    }
+
+    /// Check if a file or folder `path` should be ignored.
+    fn is_ignored(&self, path: &Path) -> bool {
+        let name = match path.file_name() {
+            Some(name) => name.to_string_lossy(),
+            None => return false,
+        };
+        self.ignore.iter().any(|pattern| glob_match(pattern, &name))
+    }
+
+    /// Return the modification time and size of each watched file.
+    fn scan(&self) -> BTreeMap<PathBuf, (SystemTime, u64)> {
+        let mut snapshot = BTreeMap::new();
+        let mut pending: Vec<PathBuf> = self.paths.clone();
+
+        while let Some(path) = pending.pop() {
+            let metadata = match fs::metadata(&path) {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+
+            if metadata.is_dir() {
+                if let Ok(entries) = fs::read_dir(&path) {
+                    pending.extend(
+                        entries
+                            .filter_map(|e| e.ok().map(|e| e.path()))
+                            .filter(|p| !self.is_ignored(p)),
+                    );
+                }
+            } else {
+                let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                snapshot.insert(path, (modified, metadata.len()));
+            }
+        }
+
+        snapshot
+    }
+
+    /// Check if any watched files were added, removed, or modified since
+    /// the last check.
+    fn changed(&mut self) -> bool {
+        let snapshot = self.scan();
+        if snapshot == self.snapshot {
+            return false;
+        }
+        self.snapshot = snapshot;
+        true
+    }
+
+    /// Wait until there are no further changes for the `DEBOUNCE` period,
+    /// i.e. while an editor is saving several files.
+    fn debounce(&mut self) {
+        loop {
+            sleep(DEBOUNCE);
+            if !self.changed() {
+                break;
+            }
+        }
+    }
+
+    /// Block until any of the watched files change.
+    fn wait_for_change(&mut self) {
+        while !self.changed() {
+            sleep(POLL_INTERVAL);
+        }
+        self.debounce();
+    }
+}
+
+/// Return the files and folders to watch for an `example`, in a Cargo
+/// project at `root`.
+fn watch_paths(example: &ExampleFile, root: &Path) -> Vec<PathBuf> {
+    let mut paths = match example.path_type {
+        // the single file
+        ExampleType::Simple | ExampleType::Custom => vec![example.path.clone()],
+        // the multi-file folder, or the nested crate
+        ExampleType::MultiFile | ExampleType::Crate(..) => vec![example.dir()],
+    };
+
+    paths.extend(
+        ["src", CARGO_TOML, "build.rs"]
+            .iter()
+            .map(|p| root.join(p))
+            .filter(|p| p.exists()),
+    );
+
+    paths
+}
+
+/// Print a *status* message for watch mode.
+fn print_status(msg: &str) {
+    println!("{} {}", "[watch]".cyan().bold(), msg.dimmed());
+}
+
+/// Run an example in *watch mode*: the example is rerun with the same
+/// arguments whenever its source files change, killing it first if it's
+/// still running.
+///
+/// This runs until `rx` (or the example) is interrupted, i.e. with Ctrl-C.
+///
+/// # Arguments
+///
+/// * `cargo` - options to pass to `cargo build`.
+/// * `example` - the example to run.
+/// * `root` - the base path to the Cargo directory.
+/// * `name` - the name of the example to run.
+/// * `args` - arguments to pass to the example.
+/// * `opts` - additional options for running the example.
+/// * `ignore` - glob patterns for file or folder names to ignore.
+pub(crate) fn watch_example(
+    cargo: &CommonOptions,
+    example: &ExampleFile,
+    root: &Path,
+    name: &str,
+    args: &[&str],
+    opts: &RunOptions,
+    ignore: &[String],
+) -> Result<()> {
+    let mut watcher = Watcher::new(watch_paths(example, root), ignore);
+
+    // Run the executable directly, so there's only one process to kill
+    let opts = RunOptions {
+        exec: true,
+        ..opts.clone()
+    };
+
+    loop {
+        let spawned = cargo.spawn_example(
+            &example.path_type,
+            root,
+            name,
+            args,
+            &example.required_features,
+            &opts,
+        )?;
+
+        let status = match spawned {
+            Spawned::Exited(build) => Some(build.status),
+            Spawned::Running(mut child, _) => loop {
+                if wait_for_exit(&mut child, false)? {
+                    detach_process(&child);
+                    let status = child.wait()?;
+                    // stop watching once the example is interrupted
+                    if interrupt_signal(&status).is_some() {
+                        return Ok(());
+                    }
+                    break Some(status);
+                }
+                if watcher.changed() {
+                    watcher.debounce();
+                    // the example may exit in the meantime
+                    kill_process_group(&mut child);
+                    wait_for_exit(&mut child, true)?;
+                    detach_process(&child);
+                    child.wait()?;
+                    break None;
+                }
+                sleep(POLL_INTERVAL);
+            },
+        };
+
+        match status {
+            Some(status) => {
+                print_status(&format!(
+                    "example exited with {status}; waiting for changes..."
+                ));
+                watcher.wait_for_change();
+            }
+            None => print_status("files changed; restarting the example..."),
+        }
+    }
+}